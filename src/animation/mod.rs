@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+
+pub mod plugin;
+pub mod systems;
+
+/// Below this many pixels per second a character is drawn standing still.
+pub const WALK_THRESHOLD: f32 = 5.;
+
+/// Which way a character looks. Sheets with fewer directions pick the closest one they have.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Facing {
+  #[default]
+  Down,
+  DownLeft,
+  Left,
+  UpLeft,
+  Up,
+  UpRight,
+  Right,
+  DownRight,
+}
+
+impl Facing {
+  /// In the order of the rows of an 8 direction sheet, clockwise from [`Facing::Down`].
+  pub const ALL: [Facing; 8] = [
+    Facing::Down,
+    Facing::DownLeft,
+    Facing::Left,
+    Facing::UpLeft,
+    Facing::Up,
+    Facing::UpRight,
+    Facing::Right,
+    Facing::DownRight,
+  ];
+
+  /// The closest of the 8 directions to `direction`, if it isn't zero.
+  pub fn from_direction(direction: Vec2) -> Option<Facing> {
+    if direction == Vec2::ZERO || !direction.is_finite() {
+      return None;
+    }
+
+    // Angle from straight down, clockwise on screen (towards the left first)
+    let angle = (-direction.x).atan2(-direction.y);
+    let octant = (angle / std::f32::consts::FRAC_PI_4).round() as i32;
+    Some(Facing::ALL[octant.rem_euclid(8) as usize])
+  }
+
+  pub fn as_vec2(&self) -> Vec2 {
+    let (x, y) = match self {
+      Facing::Down => (0., -1.),
+      Facing::DownLeft => (-1., -1.),
+      Facing::Left => (-1., 0.),
+      Facing::UpLeft => (-1., 1.),
+      Facing::Up => (0., 1.),
+      Facing::UpRight => (1., 1.),
+      Facing::Right => (1., 0.),
+      Facing::DownRight => (1., -1.),
+    };
+    Vec2::new(x, y).normalize()
+  }
+
+  fn is_left(&self) -> bool {
+    matches!(self, Facing::DownLeft | Facing::Left | Facing::UpLeft)
+  }
+}
+
+/// What a character is doing, as far as drawing it is concerned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AnimationState {
+  #[default]
+  Idle,
+  Walk,
+  Attack,
+  Hurt,
+  Death,
+}
+
+impl AnimationState {
+  /// Whether the state plays once and then gives way to whatever the character is doing.
+  pub fn is_one_shot(&self) -> bool {
+    matches!(self, AnimationState::Attack | AnimationState::Hurt)
+  }
+}
+
+/// How the rows of a clip map to facings. Sprites drawn sideways face right and are mirrored to
+/// face left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Directions {
+  /// A single sideways row.
+  One,
+  /// Down, sideways and up rows, like the mystic_woods player.
+  Three,
+  /// A row per [`Facing`], in the order of [`Facing::ALL`], like the 8-Directional Gameboy
+  /// template.
+  Eight,
+}
+
+impl Directions {
+  /// The row offset and whether to mirror the sprite for `facing`.
+  pub fn row(&self, facing: Facing) -> (usize, bool) {
+    match self {
+      Directions::One => (0, facing.is_left()),
+      Directions::Three => match facing {
+        Facing::Down => (0, false),
+        Facing::Up => (2, false),
+        _ => (1, facing.is_left()),
+      },
+      Directions::Eight => (
+        Facing::ALL
+          .iter()
+          .position(|other| *other == facing)
+          .unwrap_or(0),
+        false,
+      ),
+    }
+  }
+
+  fn len(&self) -> usize {
+    match self {
+      Directions::One => 1,
+      Directions::Three => 3,
+      Directions::Eight => 8,
+    }
+  }
+}
+
+/// A run of `frames` frames starting at the first column of `row`, repeated for every direction
+/// on the rows below.
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationClip {
+  pub state: AnimationState,
+  pub row: usize,
+  pub frames: usize,
+  pub fps: f32,
+  pub looping: bool,
+  pub directions: Directions,
+}
+
+impl AnimationClip {
+  /// The atlas index of `frame` and whether to mirror it, when looking towards `facing`.
+  pub fn index(&self, columns: usize, facing: Facing, frame: usize) -> (usize, bool) {
+    let (row, flip_x) = self.directions.row(facing);
+    (
+      (self.row + row) * columns + frame.min(self.frames - 1),
+      flip_x,
+    )
+  }
+
+  pub fn duration(&self) -> f32 {
+    self.frames as f32 / self.fps
+  }
+
+  fn contains(&self, columns: usize, index: usize) -> bool {
+    let (row, column) = (index / columns, index % columns);
+    row >= self.row && row < self.row + self.directions.len() && column < self.frames
+  }
+}
+
+/// The clips of a sprite sheet, matched against the image of a sprite by the end of its path.
+#[derive(Debug)]
+pub struct AnimationSheet {
+  pub path: &'static str,
+  pub columns: usize,
+  pub clips: &'static [AnimationClip],
+}
+
+impl AnimationSheet {
+  /// The sheet `path` belongs to, provided the sprite currently shows one of its frames. Sheets
+  /// also hold props, which stay still.
+  pub fn find(path: &str, index: usize) -> Option<&'static AnimationSheet> {
+    ANIMATION_SHEETS.iter().find(|sheet| {
+      path.ends_with(sheet.path)
+        && sheet
+          .clips
+          .iter()
+          .any(|clip| clip.contains(sheet.columns, index))
+    })
+  }
+
+  /// The clip for `state`. Walking falls back to idling, the others aren't played without a clip.
+  pub fn clip(&self, state: AnimationState) -> Option<&'static AnimationClip> {
+    let find = |state| self.clips.iter().find(|clip| clip.state == state);
+    match state {
+      AnimationState::Walk => find(state).or_else(|| find(AnimationState::Idle)),
+      _ => find(state),
+    }
+  }
+}
+
+const fn clip(
+  state: AnimationState,
+  row: usize,
+  frames: usize,
+  fps: f32,
+  directions: Directions,
+) -> AnimationClip {
+  AnimationClip {
+    state,
+    row,
+    frames,
+    fps,
+    looping: matches!(state, AnimationState::Idle | AnimationState::Walk),
+    directions,
+  }
+}
+
+/// Every sheet the game knows how to animate.
+pub const ANIMATION_SHEETS: &[AnimationSheet] = &[
+  AnimationSheet {
+    path: "8-Directional Gameboy Character Template/loose sprites.png",
+    columns: 8,
+    clips: &[
+      clip(AnimationState::Idle, 0, 1, 1., Directions::Eight),
+      clip(AnimationState::Walk, 0, 4, 8., Directions::Eight),
+    ],
+  },
+  AnimationSheet {
+    path: "mystic_woods/characters/player.png",
+    columns: 6,
+    clips: &[
+      clip(AnimationState::Idle, 0, 6, 8., Directions::Three),
+      clip(AnimationState::Walk, 3, 6, 10., Directions::Three),
+      clip(AnimationState::Attack, 6, 4, 12., Directions::Three),
+      clip(AnimationState::Death, 9, 3, 6., Directions::One),
+    ],
+  },
+  AnimationSheet {
+    path: "mystic_woods/characters/skeleton.png",
+    columns: 6,
+    clips: &[
+      clip(AnimationState::Idle, 0, 6, 8., Directions::One),
+      clip(AnimationState::Walk, 1, 6, 10., Directions::One),
+      clip(AnimationState::Attack, 2, 5, 12., Directions::One),
+      clip(AnimationState::Hurt, 3, 3, 10., Directions::One),
+      clip(AnimationState::Death, 4, 4, 6., Directions::One),
+    ],
+  },
+  AnimationSheet {
+    path: "mystic_woods/characters/slime.png",
+    columns: 7,
+    clips: &[
+      clip(AnimationState::Idle, 0, 4, 6., Directions::One),
+      clip(AnimationState::Walk, 1, 6, 10., Directions::One),
+      clip(AnimationState::Attack, 2, 7, 12., Directions::One),
+      clip(AnimationState::Hurt, 3, 3, 10., Directions::One),
+      clip(AnimationState::Death, 4, 5, 8., Directions::One),
+    ],
+  },
+];
+
+/// Plays the clips of a character's [`AnimationSheet`] on its `TextureAtlasSprite`. The sheet is
+/// found when the sprite spawns; characters drawn from an unknown sheet keep their frame.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Animator {
+  pub sheet: Option<&'static AnimationSheet>,
+  pub state: AnimationState,
+  /// The state played once over `state`, such as an attack.
+  pub one_shot: Option<AnimationState>,
+  elapsed: f32,
+  last_position: Option<Vec2>,
+}
+
+impl Animator {
+  fn playing(&self) -> AnimationState {
+    self.one_shot.unwrap_or(self.state)
+  }
+}
+
+/// Plays `state` once on `entity`, for actions only the code performing them knows about.
+#[derive(Clone, Copy, Debug)]
+pub struct PlayAnimation {
+  pub entity: Entity,
+  pub state: AnimationState,
+}
+
+/// What remains of a character after it dies, playing its death clip before disappearing.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Corpse;
@@ -0,0 +1,13 @@
+use bevy::prelude::{App, Plugin};
+
+use super::PlayAnimation;
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_event::<PlayAnimation>()
+      .add_system_set(super::systems::add_systems());
+  }
+}
@@ -0,0 +1,279 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::IntoConditionalSystem;
+
+use crate::{
+  combat::{DamageEvent, Dead, DeathEvent, Target},
+  enemy::state_machine::{Follow, Idle},
+  faction::Faction,
+  navigation::Offscreen,
+  player::Player,
+  GameState,
+};
+
+use super::{
+  AnimationSheet, AnimationState, Animator, Corpse, Facing, PlayAnimation, WALK_THRESHOLD,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("animation")
+    .with_system(attach_animation_sheets.label("animation-attach-sheets"))
+    .with_system(
+      select_animation_states
+        .run_in_state(GameState::Playing)
+        .label("animation-select-states")
+        .after("animation-attach-sheets"),
+    )
+    .with_system(
+      play_one_shots
+        .run_in_state(GameState::Playing)
+        .label("animation-play-one-shots")
+        .after("animation-select-states")
+        .after("combat"),
+    )
+    .with_system(
+      spawn_corpses
+        .run_in_state(GameState::Playing)
+        .label("animation-spawn-corpses")
+        .after("combat"),
+    )
+    .with_system(
+      animate_sprites
+        .run_in_state(GameState::Playing)
+        .label("animation-animate-sprites")
+        .after("animation-play-one-shots")
+        .after("animation-spawn-corpses"),
+    )
+}
+
+/// Looks up the [`AnimationSheet`] of every new character sprite from the path of its image.
+fn attach_animation_sheets(
+  mut animators: Query<
+    (&mut Animator, &Handle<TextureAtlas>, &TextureAtlasSprite),
+    Added<Handle<TextureAtlas>>,
+  >,
+  texture_atlases: Res<Assets<TextureAtlas>>,
+  asset_server: Res<AssetServer>,
+) {
+  for (mut animator, texture_atlas_handle, sprite) in animators.iter_mut() {
+    let Some(path) = texture_atlases
+      .get(texture_atlas_handle)
+      .and_then(|texture_atlas| asset_server.get_handle_path(&texture_atlas.texture))
+    else {
+      continue;
+    };
+
+    animator.sheet = AnimationSheet::find(&path.path().to_string_lossy(), sprite.index);
+  }
+}
+
+type AnimatedGet<'a> = (
+  &'a mut Animator,
+  &'a mut Facing,
+  &'a GlobalTransform,
+  Option<&'a Follow>,
+  Option<&'a Target>,
+  Option<&'a Idle>,
+  Option<&'a Dead>,
+  Option<&'a Offscreen>,
+);
+
+/// Picks what each character looks like it's doing: walking towards where it moved since the last
+/// frame, idling, or dying. Chasing enemies that are held back keep looking at their target.
+fn select_animation_states(
+  mut animated: Query<AnimatedGet, Without<Corpse>>,
+  targets: Query<&GlobalTransform>,
+  time: Res<Time>,
+) {
+  let delta_seconds = time.delta_seconds();
+
+  // Characters without a sheet still keep track of their facing, which interactions rely on
+  for (mut animator, mut facing, transform, follow, target, idle, dead, offscreen) in
+    animated.iter_mut()
+  {
+    let position = transform.translation().truncate();
+
+    // Off-screen characters aren't drawn, and jump when they come back
+    if offscreen.is_some() {
+      animator.last_position = None;
+      continue;
+    }
+
+    let velocity = match animator.last_position {
+      Some(last_position) if delta_seconds > 0. => (position - last_position) / delta_seconds,
+      _ => Vec2::ZERO,
+    };
+    animator.last_position = Some(position);
+
+    if dead.is_some() {
+      animator.one_shot = None;
+      set_state(&mut animator, AnimationState::Death);
+      continue;
+    }
+
+    let moving = velocity.length() > WALK_THRESHOLD;
+    let looking_at = if moving {
+      Some(velocity)
+    } else {
+      follow
+        .and(target)
+        .and_then(|&Target(target)| target)
+        .and_then(|target| targets.get(target).ok())
+        .map(|target_transform| target_transform.translation().truncate() - position)
+    };
+
+    if let Some(new_facing) = looking_at.and_then(Facing::from_direction) {
+      if *facing != new_facing {
+        *facing = new_facing;
+      }
+    }
+
+    let state = if moving && idle.is_none() {
+      AnimationState::Walk
+    } else {
+      AnimationState::Idle
+    };
+    set_state(&mut animator, state);
+  }
+}
+
+fn set_state(animator: &mut Animator, state: AnimationState) {
+  if animator.state != state {
+    animator.state = state;
+    if animator.one_shot.is_none() {
+      animator.elapsed = 0.;
+    }
+  }
+}
+
+/// Plays the hurt clip of characters taking a hit, and whatever [`PlayAnimation`] asks for.
+fn play_one_shots(
+  mut damage_events: EventReader<DamageEvent>,
+  mut play_events: EventReader<PlayAnimation>,
+  mut animators: Query<&mut Animator, Without<Dead>>,
+) {
+  // Damage dealt a little every frame, like spikes, doesn't count as a hit
+  let hits = damage_events
+    .iter()
+    .filter(|damage_event| damage_event.amount >= 1.)
+    .map(|damage_event| (damage_event.target, AnimationState::Hurt));
+  let played = play_events
+    .iter()
+    .map(|play_event| (play_event.entity, play_event.state));
+
+  for (entity, state) in hits.chain(played) {
+    let Ok(mut animator) = animators.get_mut(entity) else {
+      continue;
+    };
+
+    let has_clip = animator.sheet.and_then(|sheet| sheet.clip(state)).is_some();
+    if has_clip && state.is_one_shot() {
+      animator.one_shot = Some(state);
+      animator.elapsed = 0.;
+    }
+  }
+}
+
+/// Leaves a [`Corpse`] playing the death clip of characters that die, since they are despawned
+/// right away. The player stays around, so it plays its own.
+fn spawn_corpses(
+  mut commands: Commands,
+  mut death_events: EventReader<DeathEvent>,
+  animators: Query<
+    (
+      &Animator,
+      &Facing,
+      &TextureAtlasSprite,
+      &Handle<TextureAtlas>,
+      &GlobalTransform,
+      Option<&Faction>,
+    ),
+    Without<Player>,
+  >,
+) {
+  for death_event in death_events.iter() {
+    let Ok((animator, facing, sprite, texture_atlas, transform, faction)) =
+      animators.get(death_event.entity)
+    else {
+      continue;
+    };
+
+    let Some(sheet) = animator
+      .sheet
+      .filter(|sheet| sheet.clip(AnimationState::Death).is_some())
+    else {
+      continue;
+    };
+
+    let mut corpse = commands.spawn((
+      SpriteSheetBundle {
+        sprite: sprite.clone(),
+        texture_atlas: texture_atlas.clone(),
+        transform: transform.compute_transform(),
+        ..default()
+      },
+      Animator {
+        sheet: Some(sheet),
+        state: AnimationState::Death,
+        ..default()
+      },
+      *facing,
+      Corpse,
+      Name::new("Corpse"),
+    ));
+
+    // Lets members of the same faction recognize it
+    if let Some(faction) = faction {
+      corpse.insert(*faction);
+    }
+  }
+}
+
+/// Advances the clip each character is playing and shows its current frame. One-shot clips give
+/// way to the character's state when they end, and corpses disappear.
+fn animate_sprites(
+  mut commands: Commands,
+  mut animated: Query<(
+    Entity,
+    &mut Animator,
+    &Facing,
+    &mut TextureAtlasSprite,
+    Option<&Corpse>,
+  )>,
+  time: Res<Time>,
+) {
+  for (entity, mut animator, facing, mut sprite, corpse) in animated.iter_mut() {
+    let Some(sheet) = animator.sheet else {
+      continue;
+    };
+
+    let Some(clip) = sheet.clip(animator.playing()) else {
+      continue;
+    };
+
+    animator.elapsed += time.delta_seconds();
+    if clip.looping {
+      animator.elapsed %= clip.duration();
+    } else if animator.elapsed >= clip.duration() {
+      if corpse.is_some() {
+        commands.entity(entity).despawn_recursive();
+        continue;
+      }
+
+      // Keep the last frame until the state's clip starts next frame
+      if animator.one_shot.take().is_some() {
+        animator.elapsed = 0.;
+        continue;
+      }
+    }
+
+    let frame = (animator.elapsed * clip.fps) as usize;
+    let (index, flip_x) = clip.index(sheet.columns, *facing, frame);
+    if sprite.index != index {
+      sprite.index = index;
+    }
+    if sprite.flip_x != flip_x {
+      sprite.flip_x = flip_x;
+    }
+  }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_asset_loader::prelude::*;
+use serde::Deserialize;
+
+pub mod plugin;
+pub mod systems;
+
+/// Seconds a speech bubble stays up.
+pub const BUBBLE_SECONDS: f32 = 2.5;
+
+pub const BUBBLE_FONT_SIZE: f32 = 8.;
+
+/// How far above its speaker a speech bubble floats, in pixels.
+pub const BUBBLE_OFFSET: f32 = 16.;
+
+pub const BUBBLE_COLOR: Color = Color::rgba(1., 1., 1., 0.85);
+pub const BUBBLE_TEXT_COLOR: Color = Color::rgb(0.1, 0.1, 0.12);
+
+/// The table used by speakers whose archetype and faction have none of their own.
+pub const DEFAULT_BARKS: &str = "default";
+
+/// The AI events a character can bark about.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+pub enum BarkTrigger {
+  /// Started chasing its target.
+  Spotted,
+  /// Lost its target and went looking where it was last seen.
+  LostTarget,
+  /// Went to check on a noise.
+  HeardNoise,
+  /// Took a blow from someone.
+  Hurt,
+  /// Came across the corpse of a member of its faction.
+  AllyDown,
+}
+
+/// The lines said on a trigger, one picked at random. A bark cuts off the bubble of one with the
+/// same or a lower `priority`, and the same trigger stays quiet for `cooldown` seconds afterwards.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BarkLines {
+  #[serde(default)]
+  pub priority: u8,
+  pub cooldown: f32,
+  pub lines: Vec<String>,
+}
+
+/// The barks of an archetype, loaded from `assets/barks/<archetype>.barks.ron`. Factions can have
+/// a table too, named after them in lowercase (e.g. `guards`).
+#[derive(Clone, Debug, Default, Deserialize, TypeUuid)]
+#[serde(transparent)]
+#[uuid = "5e2f7a91-3c84-4d0b-a6e3-9b1d8f4c2a57"]
+pub struct BarkTable(pub HashMap<BarkTrigger, BarkLines>);
+
+#[derive(AssetCollection, Resource)]
+pub struct BarkAssets {
+  #[asset(
+    paths(
+      "barks/default.barks.ron",
+      "barks/skeleton.barks.ron",
+      "barks/guards.barks.ron",
+      "barks/bandits.barks.ron"
+    ),
+    collection(typed)
+  )]
+  pub tables: Vec<Handle<BarkTable>>,
+}
+
+impl BarkAssets {
+  /// The handle of the table named `name`, after its file.
+  pub fn table(&self, asset_server: &AssetServer, name: &str) -> Option<&Handle<BarkTable>> {
+    self.tables.iter().find(|handle| {
+      asset_server.get_handle_path(*handle).map_or(false, |path| {
+        path
+          .path()
+          .file_name()
+          .and_then(|file_name| file_name.to_str())
+          .and_then(|file_name| file_name.strip_suffix(".barks.ron"))
+          == Some(name)
+      })
+    })
+  }
+}
+
+/// Lets a character bark, keeping track of its cooldowns and of the bubble it's showing.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Barker {
+  pub cooldowns: HashMap<BarkTrigger, f32>,
+  pub bubble: Option<ActiveBubble>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ActiveBubble {
+  pub entity: Entity,
+  pub priority: u8,
+  /// Seconds until the bubble goes away.
+  pub remaining: f32,
+}
+
+/// The world-space bubble showing a bark, a child of its speaker.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct SpeechBubble;
+
+/// Asks `speaker` to bark about `trigger`, which it may not if it's on cooldown or busy saying
+/// something more important.
+#[derive(Clone, Copy, Debug)]
+pub struct BarkEvent {
+  pub speaker: Entity,
+  pub trigger: BarkTrigger,
+}
@@ -0,0 +1,16 @@
+use bevy::prelude::{App, Plugin};
+
+use crate::utils::ron_asset::RonAssetAppExt;
+
+use super::{BarkEvent, BarkTable};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_ron_asset::<BarkTable>(&["barks.ron"])
+      .add_event::<BarkEvent>()
+      .add_system_set(super::systems::add_systems());
+  }
+}
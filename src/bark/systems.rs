@@ -0,0 +1,282 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  animation::Corpse,
+  blackboard::{Blackboard, FactKey},
+  combat::{DamageEvent, Dead, Health},
+  enemy::{
+    state_machine::{Follow, Investigate},
+    EnemyArchetype,
+  },
+  faction::Faction,
+  navigation::Offscreen,
+  targeting::PERCEPTION_RANGE,
+  ui::UiAssets,
+  GameState,
+};
+
+use super::{
+  ActiveBubble, BarkAssets, BarkEvent, BarkTable, BarkTrigger, Barker, SpeechBubble, BUBBLE_COLOR,
+  BUBBLE_FONT_SIZE, BUBBLE_OFFSET, BUBBLE_SECONDS, BUBBLE_TEXT_COLOR, DEFAULT_BARKS,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("bark")
+    .with_system(
+      bark_on_states
+        .run_in_state(GameState::Playing)
+        .label("bark-on-states")
+        .before("bark-say"),
+    )
+    .with_system(
+      bark_on_hurt
+        .run_in_state(GameState::Playing)
+        .label("bark-on-hurt")
+        .after("combat-apply-damage")
+        .before("bark-say"),
+    )
+    .with_system(
+      bark_on_corpses
+        .run_in_state(GameState::Playing)
+        .label("bark-on-corpses")
+        .after("blackboard-age")
+        .before("bark-say"),
+    )
+    .with_system(
+      tick_barks
+        .run_in_state(GameState::Playing)
+        .label("bark-tick"),
+    )
+    .with_system(
+      say_barks
+        .run_in_state(GameState::Playing)
+        .label("bark-say")
+        .after("bark-tick"),
+    )
+}
+
+/// Barks when a character starts chasing someone, or starts investigating, depending on whether
+/// it lost its target or heard something.
+fn bark_on_states(
+  mut bark_events: EventWriter<BarkEvent>,
+  followers: Query<Entity, (Added<Follow>, With<Barker>)>,
+  investigators: Query<(Entity, &Investigate, &Blackboard), (Added<Investigate>, With<Barker>)>,
+) {
+  for speaker in followers.iter() {
+    bark_events.send(BarkEvent {
+      speaker,
+      trigger: BarkTrigger::Spotted,
+    });
+  }
+
+  for (speaker, investigate, blackboard) in investigators.iter() {
+    let trigger = match blackboard.lead(investigate.max_age) {
+      Some((FactKey::LastHeardNoise, _)) => BarkTrigger::HeardNoise,
+      _ => BarkTrigger::LostTarget,
+    };
+
+    bark_events.send(BarkEvent { speaker, trigger });
+  }
+}
+
+/// Barks when a character survives a blow. Damage dealt a little every frame, like spikes, doesn't
+/// count.
+fn bark_on_hurt(
+  mut damage_events: EventReader<DamageEvent>,
+  mut bark_events: EventWriter<BarkEvent>,
+  barkers: Query<&Health, With<Barker>>,
+) {
+  for damage_event in damage_events.iter() {
+    if damage_event.source.is_none() || damage_event.amount < 1. {
+      continue;
+    }
+
+    if barkers
+      .get(damage_event.target)
+      .map_or(false, |health| !health.is_dead())
+    {
+      bark_events.send(BarkEvent {
+        speaker: damage_event.target,
+        trigger: BarkTrigger::Hurt,
+      });
+    }
+  }
+}
+
+type WitnessGet<'a> = (Entity, &'a Faction, &'a GlobalTransform, &'a mut Blackboard);
+type WitnessWhen = (With<Barker>, Without<Dead>, Without<Offscreen>);
+
+/// Barks when a character first comes across the corpse of a member of its faction, writing it on
+/// its [`Blackboard`] so it isn't mourned twice.
+fn bark_on_corpses(
+  mut bark_events: EventWriter<BarkEvent>,
+  mut witnesses: Query<WitnessGet, WitnessWhen>,
+  corpses: Query<(Entity, &Faction, &GlobalTransform), With<Corpse>>,
+) {
+  if corpses.is_empty() {
+    return;
+  }
+
+  for (speaker, faction, transform, mut blackboard) in witnesses.iter_mut() {
+    let position = transform.translation().truncate();
+    let mut spotted = false;
+
+    for (corpse, corpse_faction, corpse_transform) in corpses.iter() {
+      let corpse_position = corpse_transform.translation().truncate();
+      if corpse_faction != faction
+        || corpse_position.distance(position) > PERCEPTION_RANGE
+        || blackboard.recall(&FactKey::SawCorpse(corpse)).is_some()
+      {
+        continue;
+      }
+
+      blackboard.remember(FactKey::SawCorpse(corpse), corpse_position);
+      spotted = true;
+    }
+
+    if spotted {
+      bark_events.send(BarkEvent {
+        speaker,
+        trigger: BarkTrigger::AllyDown,
+      });
+    }
+  }
+}
+
+/// Counts down cooldowns, and takes bubbles down once they have been up long enough.
+fn tick_barks(mut commands: Commands, mut barkers: Query<&mut Barker>, time: Res<Time>) {
+  let delta_seconds = time.delta_seconds();
+
+  for mut barker in barkers.iter_mut() {
+    if barker.cooldowns.is_empty() && barker.bubble.is_none() {
+      continue;
+    }
+
+    barker.cooldowns.retain(|_, remaining| {
+      *remaining -= delta_seconds;
+      *remaining > 0.
+    });
+
+    let expired = barker.bubble.as_mut().map_or(false, |bubble| {
+      bubble.remaining -= delta_seconds;
+      bubble.remaining <= 0.
+    });
+    if expired {
+      if let Some(bubble) = barker.bubble.take() {
+        commands.entity(bubble.entity).despawn_recursive();
+      }
+    }
+  }
+}
+
+type SpeakerGet<'a> = (
+  &'a mut Barker,
+  Option<&'a EnemyArchetype>,
+  Option<&'a Faction>,
+);
+
+/// Says a random line of the speaker's table for each bark, looking it up by archetype, then by
+/// faction, then in the default table.
+fn say_barks(
+  mut commands: Commands,
+  mut bark_events: EventReader<BarkEvent>,
+  mut speakers: Query<SpeakerGet, Without<Dead>>,
+  bark_assets: Res<BarkAssets>,
+  bark_tables: Res<Assets<BarkTable>>,
+  asset_server: Res<AssetServer>,
+  ui_assets: Res<UiAssets>,
+) {
+  for &BarkEvent { speaker, trigger } in bark_events.iter() {
+    let Ok((mut barker, archetype, faction)) = speakers.get_mut(speaker) else {
+      continue;
+    };
+
+    if barker.cooldowns.contains_key(&trigger) {
+      continue;
+    }
+
+    let table_names = [
+      archetype.and_then(|EnemyArchetype(archetype)| archetype.clone()),
+      faction.map(|faction| format!("{faction:?}").to_lowercase()),
+      Some(DEFAULT_BARKS.to_string()),
+    ];
+    let Some(lines) = table_names
+      .iter()
+      .flatten()
+      .filter_map(|name| bark_assets.table(&asset_server, name))
+      .filter_map(|handle| bark_tables.get(handle))
+      .find_map(|table| table.0.get(&trigger))
+      .filter(|lines| !lines.lines.is_empty())
+    else {
+      continue;
+    };
+
+    if barker
+      .bubble
+      .map_or(false, |bubble| bubble.priority > lines.priority)
+    {
+      continue;
+    }
+
+    if let Some(bubble) = barker.bubble.take() {
+      commands.entity(bubble.entity).despawn_recursive();
+    }
+
+    let line = &lines.lines[fastrand::usize(..lines.lines.len())];
+    barker.bubble = Some(ActiveBubble {
+      entity: spawn_bubble(&mut commands, speaker, &ui_assets, line),
+      priority: lines.priority,
+      remaining: BUBBLE_SECONDS,
+    });
+    barker.cooldowns.insert(trigger, lines.cooldown);
+  }
+}
+
+fn spawn_bubble(
+  commands: &mut Commands,
+  speaker: Entity,
+  ui_assets: &UiAssets,
+  line: &str,
+) -> Entity {
+  // Text is only measured once it's laid out, so the background is sized by its characters
+  let size = Vec2::new(
+    line.chars().count() as f32 * BUBBLE_FONT_SIZE * 0.6 + 6.,
+    BUBBLE_FONT_SIZE + 4.,
+  );
+
+  let bubble = commands
+    .spawn((
+      SpriteBundle {
+        sprite: Sprite {
+          color: BUBBLE_COLOR,
+          custom_size: Some(size),
+          ..default()
+        },
+        transform: Transform::from_xyz(0., BUBBLE_OFFSET, 10.),
+        ..default()
+      },
+      SpeechBubble,
+      Name::new("SpeechBubble"),
+    ))
+    .with_children(|bubble| {
+      bubble.spawn(Text2dBundle {
+        text: Text::from_section(
+          line,
+          TextStyle {
+            font: ui_assets.font.clone(),
+            font_size: BUBBLE_FONT_SIZE,
+            color: BUBBLE_TEXT_COLOR,
+          },
+        )
+        .with_alignment(TextAlignment::CENTER),
+        transform: Transform::from_xyz(0., 0., 0.1),
+        ..default()
+      });
+    })
+    .id();
+
+  commands.entity(speaker).add_child(bubble);
+  bubble
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+pub mod plugin;
+pub mod systems;
+
+/// How far a fight can be heard, in pixels.
+pub const FIGHT_NOISE_RADIUS: f32 = 200.;
+
+/// What a fact is about. A blackboard holds at most one fact per key, the latest.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FactKey {
+  /// Where an entity was last seen.
+  LastSeen(Entity),
+  /// Where the last noise came from.
+  LastHeardNoise,
+  /// Where a corpse was found.
+  SawCorpse(Entity),
+}
+
+impl FactKey {
+  /// Seconds a fact is remembered for before it's forgotten.
+  pub fn lifetime(&self) -> f32 {
+    match self {
+      FactKey::LastSeen(_) => 20.,
+      FactKey::LastHeardNoise => 8.,
+      FactKey::SawCorpse(_) => 60.,
+    }
+  }
+
+  /// Whether the fact points at a place worth investigating.
+  pub fn is_lead(&self) -> bool {
+    matches!(self, FactKey::LastSeen(_) | FactKey::LastHeardNoise)
+  }
+}
+
+/// Where a fact took place, along with how many seconds of play ago it was last written. Who hurt
+/// an NPC and how much is its [`Threat`](crate::targeting::Threat) instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fact {
+  pub position: Vec2,
+  pub age: f32,
+}
+
+/// What an NPC knows about the world, for its triggers and states to read and write. Facts age
+/// only while playing and are forgotten once older than their [`FactKey::lifetime`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct Blackboard {
+  facts: HashMap<FactKey, Fact>,
+}
+
+impl Blackboard {
+  pub fn remember(&mut self, key: FactKey, position: Vec2) {
+    self.facts.insert(key, Fact { position, age: 0. });
+  }
+
+  pub fn recall(&self, key: &FactKey) -> Option<&Fact> {
+    self.facts.get(key)
+  }
+
+  pub fn forget(&mut self, key: &FactKey) {
+    self.facts.remove(key);
+  }
+
+  /// The freshest place worth investigating, no older than `max_age` seconds.
+  pub fn lead(&self, max_age: f32) -> Option<(FactKey, Vec2)> {
+    self
+      .facts
+      .iter()
+      .filter(|(key, fact)| key.is_lead() && fact.age <= max_age)
+      .min_by(|(_, fact_a), (_, fact_b)| fact_a.age.total_cmp(&fact_b.age))
+      .map(|(key, fact)| (*key, fact.position))
+  }
+
+  /// Ages every fact by `seconds` and forgets those past their lifetime.
+  pub fn age(&mut self, seconds: f32) {
+    self.facts.retain(|key, fact| {
+      fact.age += seconds;
+      fact.age <= key.lifetime()
+    });
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.facts.is_empty()
+  }
+}
+
+/// A sound made somewhere in the loaded level, heard by every NPC within `radius`.
+#[derive(Clone, Copy, Debug)]
+pub struct NoiseEvent {
+  pub position: Vec2,
+  pub radius: f32,
+}
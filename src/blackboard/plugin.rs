@@ -0,0 +1,13 @@
+use bevy::prelude::{App, Plugin};
+
+use super::NoiseEvent;
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_event::<NoiseEvent>()
+      .add_system_set(super::systems::add_systems());
+  }
+}
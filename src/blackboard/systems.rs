@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  combat::{DamageEvent, Target},
+  navigation::Offscreen,
+  GameState,
+};
+
+use super::{Blackboard, FactKey, NoiseEvent, FIGHT_NOISE_RADIUS};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("blackboard")
+    .with_system(
+      age_facts
+        .run_in_state(GameState::Playing)
+        .label("blackboard-age"),
+    )
+    .with_system(
+      remember_targets
+        .run_in_state(GameState::Playing)
+        .label("blackboard-remember-targets")
+        .after("blackboard-age")
+        .after("targeting-select-targets"),
+    )
+    .with_system(
+      remember_attackers
+        .run_in_state(GameState::Playing)
+        .label("blackboard-remember-attackers")
+        .after("blackboard-age")
+        .after("combat-apply-damage"),
+    )
+    .with_system(
+      make_fight_noise
+        .run_in_state(GameState::Playing)
+        .label("blackboard-fight-noise")
+        .after("combat-apply-damage"),
+    )
+    .with_system(
+      hear_noises
+        .run_in_state(GameState::Playing)
+        .label("blackboard-hear-noises")
+        .after("blackboard-age")
+        .after("blackboard-fight-noise"),
+    )
+}
+
+fn age_facts(mut blackboards: Query<&mut Blackboard>, time: Res<Time>) {
+  for mut blackboard in blackboards.iter_mut() {
+    if !blackboard.is_empty() {
+      blackboard.age(time.delta_seconds());
+    }
+  }
+}
+
+/// Writes down where every NPC currently sees its [`Target`].
+fn remember_targets(
+  mut watchers: Query<(&mut Blackboard, &Target), Without<Offscreen>>,
+  positions: Query<&GlobalTransform, Without<Offscreen>>,
+) {
+  for (mut blackboard, &Target(target)) in watchers.iter_mut() {
+    let Some((target, transform)) = target.and_then(|target| {
+      positions
+        .get(target)
+        .ok()
+        .map(|transform| (target, transform))
+    }) else {
+      continue;
+    };
+
+    blackboard.remember(
+      FactKey::LastSeen(target),
+      transform.translation().truncate(),
+    );
+  }
+}
+
+/// Writes down where whoever hurt an NPC stood, so it goes looking for them once it loses sight.
+fn remember_attackers(
+  mut damage_events: EventReader<DamageEvent>,
+  mut blackboards: Query<&mut Blackboard>,
+  positions: Query<&GlobalTransform>,
+) {
+  for &DamageEvent { target, source, .. } in damage_events.iter() {
+    let (Some(source), Ok(mut blackboard)) = (source, blackboards.get_mut(target)) else {
+      continue;
+    };
+
+    if let Ok(transform) = positions.get(source) {
+      blackboard.remember(
+        FactKey::LastSeen(source),
+        transform.translation().truncate(),
+      );
+    }
+  }
+}
+
+/// Blows dealt by someone can be heard around whoever took them. Damage dealt a little every frame,
+/// like spikes, makes no noise.
+fn make_fight_noise(
+  mut damage_events: EventReader<DamageEvent>,
+  mut noise_events: EventWriter<NoiseEvent>,
+  positions: Query<&GlobalTransform, Without<Offscreen>>,
+) {
+  for damage_event in damage_events.iter() {
+    if damage_event.source.is_none() || damage_event.amount < 1. {
+      continue;
+    }
+
+    if let Ok(transform) = positions.get(damage_event.target) {
+      noise_events.send(NoiseEvent {
+        position: transform.translation().truncate(),
+        radius: FIGHT_NOISE_RADIUS,
+      });
+    }
+  }
+}
+
+fn hear_noises(
+  mut noise_events: EventReader<NoiseEvent>,
+  mut listeners: Query<(&mut Blackboard, &GlobalTransform), Without<Offscreen>>,
+) {
+  for noise_event in noise_events.iter() {
+    for (mut blackboard, transform) in listeners.iter_mut() {
+      if transform
+        .translation()
+        .truncate()
+        .distance(noise_event.position)
+        <= noise_event.radius
+      {
+        blackboard.remember(FactKey::LastHeardNoise, noise_event.position);
+      }
+    }
+  }
+}
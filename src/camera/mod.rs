@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+pub mod plugin;
+pub mod systems;
+
+/// Used until the window reports its size.
+pub const DEFAULT_ASPECT_RATIO: f32 = 16. / 9.;
+
+/// How quickly the camera catches up with where it wants to be, per second.
+pub const FOLLOW_SPEED: f32 = 5.;
+pub const ROOM_SCROLL_SPEED: f32 = 8.;
+
+/// Size of the box around the center of the view the player can move in without the camera
+/// following, in pixels.
+pub const DEADZONE: Vec2 = Vec2::new(48., 32.);
+
+/// How far ahead of the player the camera looks in [`CameraMode::LookAhead`], in pixels.
+pub const LOOK_AHEAD_DISTANCE: f32 = 48.;
+
+/// How much the camera may zoom in (below 1) or out (above 1) to frame several [`CameraTarget`]s.
+pub const MIN_ZOOM: f32 = 0.6;
+pub const MAX_ZOOM: f32 = 1.5;
+pub const ZOOM_SPEED: f32 = 3.;
+/// Room left around the framed targets on every side, in pixels.
+pub const FRAMING_PADDING: f32 = 32.;
+
+/// How far the camera moves away from its focus at full trauma, in pixels.
+pub const MAX_SHAKE_OFFSET: f32 = 6.;
+/// How much trauma wears off per second.
+pub const TRAUMA_DECAY: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CameraMode {
+  /// Glued to the player.
+  Snap,
+  /// Eases towards the player once they leave the [`DEADZONE`].
+  #[default]
+  Follow,
+  /// Eases towards a point ahead of the player, in the direction they last moved.
+  LookAhead,
+  /// Splits the level in screen sized rooms and scrolls to the room the player enters.
+  RoomLock,
+}
+
+impl CameraMode {
+  pub fn next(&self) -> CameraMode {
+    match self {
+      CameraMode::Snap => CameraMode::Follow,
+      CameraMode::Follow => CameraMode::LookAhead,
+      CameraMode::LookAhead => CameraMode::RoomLock,
+      CameraMode::RoomLock => CameraMode::Snap,
+    }
+  }
+
+  pub fn label(&self) -> &'static str {
+    match self {
+      CameraMode::Snap => "snap",
+      CameraMode::Follow => "follow",
+      CameraMode::LookAhead => "look ahead",
+      CameraMode::RoomLock => "room lock",
+    }
+  }
+}
+
+/// The camera showing the level. It keeps the whole height (or width) of the level in view and
+/// moves along the other axis according to the selected [`CameraMode`].
+#[derive(Component, Clone, Debug)]
+pub struct GameCamera {
+  /// The center of the view, before shaking.
+  pub focus: Vec2,
+  /// How many times the level fitting view is shown, eased towards what framing the targets needs.
+  pub zoom: f32,
+  /// From 0 to 1, how much the camera currently shakes. The offset grows with its square.
+  pub trauma: f32,
+  level_iid: Option<String>,
+  look_ahead: Vec2,
+  last_target_position: Option<Vec2>,
+}
+
+impl Default for GameCamera {
+  fn default() -> Self {
+    Self {
+      focus: Vec2::ZERO,
+      zoom: 1.,
+      trauma: 0.,
+      level_iid: None,
+      look_ahead: Vec2::ZERO,
+      last_target_position: None,
+    }
+  }
+}
+
+/// Tags an entity the camera keeps in frame. As soon as more than one is in the loaded level, e.g.
+/// the player and a boss, the camera frames all of them instead of following the player.
+///
+/// LDtk entities with a `CameraTarget` boolean field set are tagged when they spawn.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct CameraTarget;
+
+/// Adds `trauma` to the [`GameCamera`], shaking it if screen shake is enabled in the settings.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraShake {
+  pub trauma: f32,
+}
+
+/// The size of a view with `aspect_ratio` showing the whole level along its shorter side.
+pub fn fit_view(level_size: Vec2, aspect_ratio: f32) -> Vec2 {
+  if level_size.x / level_size.y > aspect_ratio {
+    // level is wider than the screen
+    Vec2::new(level_size.y * aspect_ratio, level_size.y)
+  } else {
+    // level is taller than the screen
+    Vec2::new(level_size.x, level_size.x / aspect_ratio)
+  }
+}
+
+/// The center and padded size of the box around every position.
+pub fn frame_targets(positions: &[Vec2]) -> Option<(Vec2, Vec2)> {
+  let first = *positions.first()?;
+  let (min, max) = positions
+    .iter()
+    .fold((first, first), |(min, max), position| {
+      (min.min(*position), max.max(*position))
+    });
+
+  Some((
+    (min + max) / 2.,
+    max - min + Vec2::splat(FRAMING_PADDING * 2.),
+  ))
+}
+
+/// Moves `focus` so a view of `view_size` around it stays inside the level, centering the view on
+/// any axis the level is smaller than it.
+pub fn clamp_focus(focus: Vec2, view_size: Vec2, level_min: Vec2, level_size: Vec2) -> Vec2 {
+  let clamp_axis = |focus: f32, view: f32, min: f32, size: f32| {
+    if view >= size {
+      min + size / 2.
+    } else {
+      focus.clamp(min + view / 2., min + size - view / 2.)
+    }
+  };
+
+  Vec2::new(
+    clamp_axis(focus.x, view_size.x, level_min.x, level_size.x),
+    clamp_axis(focus.y, view_size.y, level_min.y, level_size.y),
+  )
+}
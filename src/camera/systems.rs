@@ -0,0 +1,219 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+  combat::{DamageEvent, Dead},
+  navigation::Offscreen,
+  pause::Settings,
+  player::Player,
+  utils::ldtk::field_bool,
+};
+
+use super::{
+  clamp_focus, fit_view, frame_targets, CameraMode, CameraShake, CameraTarget, GameCamera,
+  DEADZONE, DEFAULT_ASPECT_RATIO, FOLLOW_SPEED, LOOK_AHEAD_DISTANCE, MAX_SHAKE_OFFSET, MAX_ZOOM,
+  MIN_ZOOM, ROOM_SCROLL_SPEED, TRAUMA_DECAY, ZOOM_SPEED,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("camera")
+    .with_system(tag_camera_targets.label("camera-tag-targets"))
+    .with_system(shake_on_player_hit.label("camera-shake-on-player-hit"))
+    .with_system(
+      add_trauma
+        .label("camera-add-trauma")
+        .after("camera-shake-on-player-hit"),
+    )
+    .with_system(
+      camera_fit_inside_current_level
+        .label("camera-fit-inside-current-level")
+        .after("camera-add-trauma"),
+    )
+}
+
+// Factor this type out into a type alias
+type LevelQuery<'w, 's> = Query<
+  'w,
+  's,
+  (&'static Transform, &'static Handle<LdtkLevel>),
+  (Without<OrthographicProjection>, Without<Player>),
+>;
+
+type TargetWhen = (With<CameraTarget>, Without<Offscreen>, Without<Dead>);
+
+/// Moves the camera according to the selected [`CameraMode`], or frames every [`CameraTarget`]
+/// when there are several, keeping the view inside the current level whatever the aspect ratio of
+/// the window.
+pub fn camera_fit_inside_current_level(
+  mut camera_query: Query<
+    (&mut GameCamera, &mut OrthographicProjection, &mut Transform),
+    Without<Player>,
+  >,
+  player_query: Query<&Transform, With<Player>>,
+  targets: Query<&GlobalTransform, TargetWhen>,
+  level_query: LevelQuery,
+  level_selection: Res<LevelSelection>,
+  ldtk_levels: Res<Assets<LdtkLevel>>,
+  windows: Res<Windows>,
+  settings: Res<Settings>,
+  time: Res<Time>,
+) {
+  let (Ok(player_transform), Ok((mut camera, mut orthographic_projection, mut camera_transform))) =
+    (player_query.get_single(), camera_query.get_single_mut())
+  else {
+    return;
+  };
+
+  let Some((level_transform, ldtk_level)) = level_query
+    .iter()
+    .filter_map(|(level_transform, level_handle)| {
+      ldtk_levels
+        .get(level_handle)
+        .map(|ldtk_level| (level_transform, ldtk_level))
+    })
+    .find(|(_, ldtk_level)| level_selection.is_match(&0, &ldtk_level.level))
+  else {
+    return;
+  };
+
+  let level = &ldtk_level.level;
+  let level_min = level_transform.translation.truncate();
+  let level_size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+
+  let aspect_ratio = windows
+    .get_primary()
+    .map(|window| window.width() / window.height())
+    .filter(|aspect_ratio| aspect_ratio.is_finite() && *aspect_ratio > 0.)
+    .unwrap_or(DEFAULT_ASPECT_RATIO);
+  let delta_seconds = time.delta_seconds();
+
+  let target_positions: Vec<Vec2> = targets
+    .iter()
+    .map(|target_transform| target_transform.translation().truncate())
+    .collect();
+  let framing = if target_positions.len() > 1 {
+    frame_targets(&target_positions)
+  } else {
+    None
+  };
+
+  let level_view_size = fit_view(level_size, aspect_ratio);
+  let zoom = framing
+    .map(|(_, framed_size)| {
+      (framed_size / level_view_size)
+        .max_element()
+        .clamp(MIN_ZOOM, MAX_ZOOM)
+    })
+    .unwrap_or(1.);
+  camera.zoom += (zoom - camera.zoom) * ease(ZOOM_SPEED, delta_seconds);
+  let view_size = level_view_size * camera.zoom;
+  let player_position = player_transform.translation.truncate();
+
+  if delta_seconds > 0. {
+    if let Some(last_position) = camera.last_target_position {
+      let velocity = (player_position - last_position) / delta_seconds;
+      if velocity.length() > 1. {
+        let look_ahead = velocity.normalize() * LOOK_AHEAD_DISTANCE;
+        camera.look_ahead = camera
+          .look_ahead
+          .lerp(look_ahead, ease(FOLLOW_SPEED, delta_seconds));
+      }
+    }
+  }
+  camera.last_target_position = Some(player_position);
+
+  let room = ((player_position - level_min) / view_size).floor();
+  let room_center = level_min + (room + 0.5) * view_size;
+
+  // Don't pan across the screen when arriving in a level
+  let focus = if camera.level_iid.as_deref() != Some(level.iid.as_str()) {
+    camera.level_iid = Some(level.iid.clone());
+    match settings.camera_mode {
+      CameraMode::RoomLock => room_center,
+      _ => player_position,
+    }
+  } else if let Some((framed_center, _)) = framing {
+    camera
+      .focus
+      .lerp(framed_center, ease(FOLLOW_SPEED, delta_seconds))
+  } else {
+    match settings.camera_mode {
+      CameraMode::Snap => player_position,
+      CameraMode::Follow => {
+        let offset = player_position - camera.focus;
+        let outside_deadzone = offset - offset.clamp(-DEADZONE / 2., DEADZONE / 2.);
+        camera.focus.lerp(
+          camera.focus + outside_deadzone,
+          ease(FOLLOW_SPEED, delta_seconds),
+        )
+      }
+      CameraMode::LookAhead => camera.focus.lerp(
+        player_position + camera.look_ahead,
+        ease(FOLLOW_SPEED, delta_seconds),
+      ),
+      CameraMode::RoomLock => camera
+        .focus
+        .lerp(room_center, ease(ROOM_SCROLL_SPEED, delta_seconds)),
+    }
+  };
+
+  camera.focus = clamp_focus(focus, view_size, level_min, level_size);
+  camera.trauma = (camera.trauma - TRAUMA_DECAY * delta_seconds).max(0.);
+
+  let shake = if settings.screen_shake {
+    Vec2::new(fastrand::f32() * 2. - 1., fastrand::f32() * 2. - 1.)
+      * MAX_SHAKE_OFFSET
+      * camera.trauma.powi(2)
+  } else {
+    Vec2::ZERO
+  };
+
+  orthographic_projection.scaling_mode = ScalingMode::None;
+  orthographic_projection.left = -view_size.x / 2.;
+  orthographic_projection.right = view_size.x / 2.;
+  orthographic_projection.bottom = -view_size.y / 2.;
+  orthographic_projection.top = view_size.y / 2.;
+
+  let z = camera_transform.translation.z;
+  camera_transform.translation = (camera.focus + shake).extend(z);
+}
+
+/// The fraction of the remaining distance to cover this frame to ease in at `speed`, independent
+/// of the frame rate.
+fn ease(speed: f32, delta_seconds: f32) -> f32 {
+  1. - (-speed * delta_seconds).exp()
+}
+
+fn add_trauma(mut shake_events: EventReader<CameraShake>, mut cameras: Query<&mut GameCamera>) {
+  for &CameraShake { trauma } in shake_events.iter() {
+    for mut camera in cameras.iter_mut() {
+      camera.trauma = (camera.trauma + trauma).min(1.);
+    }
+  }
+}
+
+/// Shakes the camera when the player takes a hit. Damage dealt a little every frame, like spikes,
+/// doesn't count.
+fn shake_on_player_hit(
+  mut damage_events: EventReader<DamageEvent>,
+  mut shake_events: EventWriter<CameraShake>,
+  players: Query<(), With<Player>>,
+) {
+  for damage_event in damage_events.iter() {
+    if players.contains(damage_event.target) && damage_event.amount >= 1. {
+      shake_events.send(CameraShake { trauma: 0.4 });
+    }
+  }
+}
+
+fn tag_camera_targets(
+  mut commands: Commands,
+  entities: Query<(Entity, &EntityInstance), Added<EntityInstance>>,
+) {
+  for (entity, entity_instance) in entities.iter() {
+    if field_bool(entity_instance, "CameraTarget").unwrap_or(false) {
+      commands.entity(entity).insert(CameraTarget);
+    }
+  }
+}
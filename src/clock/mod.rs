@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod plugin;
+pub mod systems;
+
+pub const MINUTES_PER_DAY: f32 = 24. * 60.;
+
+/// Game minutes that pass per real second, so a day lasts 24 real minutes.
+pub const DEFAULT_CLOCK_SPEED: f32 = 1.;
+
+/// The hour a new game starts at.
+pub const START_HOUR: f32 = 8.;
+
+/// The in-game time of day. It only runs while playing, so it stands still in menus and
+/// conversations.
+#[derive(Resource, Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GameClock {
+  /// Starting at 1.
+  pub day: u32,
+  /// Minutes since midnight.
+  pub minute: f32,
+  /// Game minutes per real second.
+  pub speed: f32,
+}
+
+impl Default for GameClock {
+  fn default() -> Self {
+    Self {
+      day: 1,
+      minute: START_HOUR * 60.,
+      speed: DEFAULT_CLOCK_SPEED,
+    }
+  }
+}
+
+impl GameClock {
+  /// Fractional hours since midnight, from 0 to 24.
+  pub fn hour(&self) -> f32 {
+    self.minute / 60.
+  }
+
+  /// Minutes since the start of the first day, for measuring spans across days.
+  pub fn total_minutes(&self) -> f32 {
+    (self.day - 1) as f32 * MINUTES_PER_DAY + self.minute
+  }
+
+  pub fn advance(&mut self, seconds: f32) {
+    self.minute += seconds * self.speed;
+    while self.minute >= MINUTES_PER_DAY {
+      self.minute -= MINUTES_PER_DAY;
+      self.day += 1;
+    }
+  }
+
+  /// Whether the clock reads between `from` and `to` hours, wrapping past midnight when `to` comes
+  /// first (e.g. 20 to 6).
+  pub fn is_between(&self, from: f32, to: f32) -> bool {
+    let hour = self.hour();
+    if from <= to {
+      hour >= from && hour < to
+    } else {
+      hour >= from || hour < to
+    }
+  }
+
+  pub fn label(&self) -> String {
+    let minute = self.minute as u32;
+    format!("Day {} {:02}:{:02}", self.day, minute / 60, minute % 60)
+  }
+}
+
+/// Shows the [`GameClock`] in a corner of the screen while playing.
+#[derive(Component)]
+pub struct ClockDisplay;
@@ -0,0 +1,24 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::{
+  systems::{despawn_clock_display, reset_clock, spawn_clock_display},
+  GameClock,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<GameClock>()
+      .add_enter_system(GameState::MainMenu, reset_clock)
+      .add_exit_system(GameState::GameOver, reset_clock)
+      .add_exit_system(GameState::Victory, reset_clock)
+      .add_enter_system(GameState::Playing, spawn_clock_display)
+      .add_exit_system(GameState::Playing, despawn_clock_display)
+      .add_system_set(super::systems::add_systems());
+  }
+}
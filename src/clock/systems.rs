@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{ui::UiAssets, GameState};
+
+use super::{ClockDisplay, GameClock};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("clock")
+    .with_system(
+      advance_clock
+        .run_in_state(GameState::Playing)
+        .label("clock-advance"),
+    )
+    .with_system(
+      update_clock_display
+        .run_in_state(GameState::Playing)
+        .label("clock-update-display")
+        .after("clock-advance"),
+    )
+}
+
+pub fn reset_clock(mut clock: ResMut<GameClock>) {
+  *clock = GameClock::default();
+}
+
+fn advance_clock(mut clock: ResMut<GameClock>, time: Res<Time>) {
+  clock.advance(time.delta_seconds());
+}
+
+pub fn spawn_clock_display(
+  mut commands: Commands,
+  ui_assets: Res<UiAssets>,
+  clock: Res<GameClock>,
+) {
+  commands.spawn((
+    ui_assets.text(clock.label(), 18.).with_style(Style {
+      position_type: PositionType::Absolute,
+      position: UiRect {
+        top: Val::Px(8.),
+        right: Val::Px(12.),
+        ..default()
+      },
+      ..default()
+    }),
+    ClockDisplay,
+  ));
+}
+
+pub fn despawn_clock_display(mut commands: Commands, displays: Query<Entity, With<ClockDisplay>>) {
+  for display in displays.iter() {
+    commands.entity(display).despawn_recursive();
+  }
+}
+
+fn update_clock_display(clock: Res<GameClock>, mut displays: Query<&mut Text, With<ClockDisplay>>) {
+  let label = clock.label();
+  for mut text in displays.iter_mut() {
+    if text.sections[0].value != label {
+      text.sections[0].value = label.clone();
+    }
+  }
+}
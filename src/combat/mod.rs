@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+pub mod plugin;
+pub mod systems;
+
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct Health {
+  pub current: f32,
+  pub max: f32,
+}
+
+impl Health {
+  pub fn new(max: f32) -> Self {
+    Self { current: max, max }
+  }
+
+  pub fn is_dead(&self) -> bool {
+    self.current <= 0.
+  }
+}
+
+impl Default for Health {
+  fn default() -> Self {
+    Self::new(10.)
+  }
+}
+
+/// Who an entity is fighting, picked by [`targeting`](crate::targeting) among the entities it
+/// perceives. AI states like [`Follow`](crate::enemy::state_machine::Follow) go after it.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Target(pub Option<Entity>);
+
+/// Hits the [`Target`] for `damage` whenever it's within `range` pixels, at most once every
+/// `cooldown` seconds.
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct MeleeAttack {
+  pub damage: f32,
+  pub range: f32,
+  pub cooldown: f32,
+  /// Seconds until the next hit can land.
+  pub ready_in: f32,
+}
+
+impl Default for MeleeAttack {
+  fn default() -> Self {
+    Self {
+      damage: 1.,
+      range: 14.,
+      cooldown: 1.,
+      ready_in: 0.,
+    }
+  }
+}
+
+/// Marks an entity whose health reached zero. It is despawned at the end of the frame, so systems
+/// reacting to [`DeathEvent`] can still read its components.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Dead;
+
+/// Deals `amount` of damage to `target`, optionally remembering who caused it.
+#[derive(Clone, Copy, Debug)]
+pub struct DamageEvent {
+  pub target: Entity,
+  pub amount: f32,
+  pub source: Option<Entity>,
+}
+
+/// Sent once when an entity's [`Health`] drops to zero.
+#[derive(Clone, Copy, Debug)]
+pub struct DeathEvent {
+  pub entity: Entity,
+  pub translation: Vec3,
+  pub killer: Option<Entity>,
+}
@@ -0,0 +1,15 @@
+use bevy::prelude::{App, CoreStage, Plugin};
+
+use super::{DamageEvent, DeathEvent};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_event::<DamageEvent>()
+      .add_event::<DeathEvent>()
+      .add_system_set(super::systems::add_systems())
+      .add_system_to_stage(CoreStage::PostUpdate, super::systems::despawn_dead);
+  }
+}
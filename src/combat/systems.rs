@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::IntoConditionalSystem;
+
+use crate::{
+  animation::{AnimationState, PlayAnimation},
+  map::terrain::TerrainKind,
+  navigation::{Offscreen, WalkabilityGrid},
+  player::Player,
+  GameState,
+};
+
+use super::{DamageEvent, Dead, DeathEvent, Health, MeleeAttack, Target};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("combat")
+    .with_system(
+      damage_from_terrain
+        .run_in_state(GameState::Playing)
+        .label("combat-terrain-damage")
+        .before("combat-apply-damage"),
+    )
+    .with_system(
+      melee_attack
+        .run_in_state(GameState::Playing)
+        .label("combat-melee-attack")
+        .before("combat-apply-damage"),
+    )
+    .with_system(
+      apply_damage
+        .run_in_state(GameState::Playing)
+        .label("combat-apply-damage"),
+    )
+}
+
+/// Hurts everything standing on damaging terrain of the loaded level, continuously.
+fn damage_from_terrain(
+  mut damage_events: EventWriter<DamageEvent>,
+  grid: Res<WalkabilityGrid>,
+  targets: Query<(Entity, &Transform), (With<Health>, Without<Dead>, Without<Offscreen>)>,
+  time: Res<Time>,
+) {
+  if grid.level_iid.is_none() {
+    return;
+  }
+
+  for (entity, transform) in targets.iter() {
+    let tile = grid.tile_at(transform.translation.truncate());
+    if let Some(TerrainKind::Damaging { damage_per_second }) = grid.terrain_at(&tile) {
+      damage_events.send(DamageEvent {
+        target: entity,
+        amount: damage_per_second * time.delta_seconds(),
+        source: None,
+      });
+    }
+  }
+}
+
+/// Lets everything with a [`MeleeAttack`] hit its [`Target`] once it's close enough.
+fn melee_attack(
+  mut damage_events: EventWriter<DamageEvent>,
+  mut play_events: EventWriter<PlayAnimation>,
+  mut attackers: Query<(Entity, &mut MeleeAttack, &Target, &GlobalTransform), Without<Dead>>,
+  targets: Query<&GlobalTransform, (With<Health>, Without<Dead>, Without<Offscreen>)>,
+  time: Res<Time>,
+) {
+  for (attacker, mut melee_attack, target, transform) in attackers.iter_mut() {
+    melee_attack.ready_in = (melee_attack.ready_in - time.delta_seconds()).max(0.);
+
+    let Some((target, target_transform)) = target.0.and_then(|target| {
+      targets
+        .get(target)
+        .ok()
+        .map(|transform| (target, transform))
+    }) else {
+      continue;
+    };
+
+    let distance = transform
+      .translation()
+      .truncate()
+      .distance(target_transform.translation().truncate());
+    if melee_attack.ready_in > 0. || distance > melee_attack.range {
+      continue;
+    }
+
+    melee_attack.ready_in = melee_attack.cooldown;
+    damage_events.send(DamageEvent {
+      target,
+      amount: melee_attack.damage,
+      source: Some(attacker),
+    });
+    play_events.send(PlayAnimation {
+      entity: attacker,
+      state: AnimationState::Attack,
+    });
+  }
+}
+
+/// Subtracts the damage of every [`DamageEvent`] from the target's health, flagging it as [`Dead`]
+/// and announcing it with a [`DeathEvent`] the first time it reaches zero.
+fn apply_damage(
+  mut commands: Commands,
+  mut damage_events: EventReader<DamageEvent>,
+  mut death_events: EventWriter<DeathEvent>,
+  mut targets: Query<(&mut Health, &GlobalTransform), Without<Dead>>,
+) {
+  for &DamageEvent {
+    target,
+    amount,
+    source,
+  } in damage_events.iter()
+  {
+    let Ok((mut health, transform)) = targets.get_mut(target) else {
+      continue;
+    };
+
+    if health.is_dead() {
+      continue;
+    }
+
+    health.current = (health.current - amount).clamp(0., health.max);
+
+    if health.is_dead() {
+      commands.entity(target).insert(Dead);
+      death_events.send(DeathEvent {
+        entity: target,
+        translation: transform.translation(),
+        killer: source,
+      });
+    }
+  }
+}
+
+/// Despawns whatever died this frame. The player is left to the game flow, which tears down the
+/// whole world once the game over screen is dismissed.
+pub fn despawn_dead(mut commands: Commands, dead: Query<Entity, (With<Dead>, Without<Player>)>) {
+  for entity in dead.iter() {
+    commands.entity(entity).despawn_recursive();
+  }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+  animation::{Animator, Facing},
+  combat::{Health, MeleeAttack, Target},
+  faction::Faction,
+  interaction::Interactable,
+  map::{ColliderBundle, WallDetection},
+  navigation::NavPath,
+  targeting::Threat,
+};
+
+pub mod plugin;
+pub mod state_machine;
+pub mod systems;
+
+/// How close to the player a following companion stays, in pixels.
+pub const COMPANION_DISTANCE: f32 = 28.;
+
+/// How far from its leader, or from where it was told to wait, a companion goes after enemies, in
+/// pixels.
+pub const COMPANION_FIGHT_RANGE: f32 = 120.;
+
+/// Further than this from the player, a following companion teleports next to them.
+pub const TELEPORT_DISTANCE: f32 = 320.;
+
+/// Seconds a following companion can fail to make progress before teleporting next to the player.
+pub const STUCK_SECONDS: f32 = 2.;
+
+/// Pixels per second under which a companion on its way to the player counts as stuck.
+pub const STUCK_SPEED: f32 = 4.;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CompanionMode {
+  #[default]
+  Following,
+  Waiting,
+}
+
+impl CompanionMode {
+  /// The prompt of the command switching to the other mode.
+  pub fn command(&self) -> &'static str {
+    match self {
+      CompanionMode::Following => "Wait here",
+      CompanionMode::Waiting => "Follow me",
+    }
+  }
+}
+
+/// An ally of the player that follows them around and fights by their side, or waits where it's
+/// told to.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Companion {
+  pub mode: CompanionMode,
+  /// Seconds it has been failing to get closer to its leader.
+  pub stuck_for: f32,
+  pub last_position: Option<Vec2>,
+  /// Where it was told to wait, while [`CompanionMode::Waiting`].
+  pub wait_spot: Option<Vec2>,
+}
+
+/// Who a follower sticks with while it has no one to fight, staying about `distance` pixels away.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Leader {
+  pub entity: Entity,
+  pub distance: f32,
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct CompanionBundle {
+  #[from_entity_instance]
+  #[bundle]
+  pub collider_bundle: ColliderBundle,
+  pub wall_detection: WallDetection,
+  pub animator: Animator,
+  pub facing: Facing,
+
+  pub companion: Companion,
+  #[from_entity_instance]
+  pub faction: Faction,
+  #[from_entity_instance]
+  pub interactable: Interactable,
+  pub controller: KinematicCharacterController,
+  pub health: Health,
+  pub target: Target,
+  pub threat: Threat,
+  pub melee_attack: MeleeAttack,
+  pub nav_path: NavPath,
+
+  #[worldly]
+  pub worldly: Worldly,
+
+  #[sprite_sheet_bundle]
+  #[bundle]
+  sprite_bundle: SpriteSheetBundle,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
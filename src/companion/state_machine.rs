@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+use seldom_state::prelude::*;
+
+use crate::{
+  combat::Target,
+  enemy::state_machine::{Follow, Idle},
+  player::PLAYER_SPEED,
+};
+
+use super::Leader;
+
+/// Holds while the entity has a [`Leader`] to follow or a [`Target`] to fight.
+#[derive(Clone, Copy, FromReflect, Reflect)]
+pub struct HasLeaderOrTarget;
+
+impl Trigger for HasLeaderOrTarget {
+  type Param<'w, 's> = Query<'w, 's, (Option<&'static Leader>, Option<&'static Target>)>;
+
+  fn trigger(&self, entity: Entity, followers: &Self::Param<'_, '_>) -> bool {
+    followers.get(entity).map_or(false, |(leader, target)| {
+      leader.is_some() || matches!(target, Some(Target(Some(_))))
+    })
+  }
+}
+
+/// Builds the state machine of a companion, following its target when it has one and its leader
+/// otherwise. Companions told to wait have no leader, but still go after targets in range.
+pub fn state_machine() -> StateMachine {
+  StateMachine::new(Idle)
+    // Idle --(has_leader_or_target)-> Follow
+    .trans::<Idle>(HasLeaderOrTarget, Follow::new(PLAYER_SPEED))
+    // Follow --(!has_leader_or_target)-> Idle
+    .trans::<Follow>(NotTrigger(HasLeaderOrTarget), Idle)
+}
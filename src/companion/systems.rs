@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use iyes_loopless::prelude::*;
+use seldom_state::prelude::*;
+
+use crate::{
+  combat::Target,
+  interaction::{InteractEvent, Interactable},
+  navigation::{InLevel, NavPath, Offscreen, WalkabilityGrid},
+  player::Player,
+  utils::ldtk::selected_level,
+  GameState,
+};
+
+use super::{
+  state_machine::state_machine, Companion, CompanionMode, Leader, COMPANION_DISTANCE,
+  COMPANION_FIGHT_RANGE, STUCK_SECONDS, STUCK_SPEED, TELEPORT_DISTANCE,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("companion")
+    .with_system(spawn.label("companion-spawn"))
+    .with_system(
+      command_companions
+        .run_in_state(GameState::Playing)
+        .label("companion-command")
+        .after("interaction-interact"),
+    )
+    .with_system(
+      update_leaders
+        .run_in_state(GameState::Playing)
+        .label("companion-update-leaders")
+        .after("companion-spawn")
+        .after("companion-command")
+        .before("enemy-follow"),
+    )
+    .with_system(
+      restrict_targets
+        .run_in_state(GameState::Playing)
+        .label("companion-restrict-targets")
+        .after("targeting-select-targets")
+        .before("enemy-follow")
+        .before("combat-melee-attack"),
+    )
+    .with_system(
+      catch_up
+        .run_in_state(GameState::Playing)
+        .label("companion-catch-up")
+        .after("enemy-follow"),
+    )
+}
+
+/// Gives every companion without one its state machine, and remembers the level it was spawned in.
+fn spawn(
+  mut commands: Commands,
+  companions: Query<Entity, (With<Companion>, Without<StateMachine>)>,
+  level_selection: Res<LevelSelection>,
+  level_query: Query<&Handle<LdtkLevel>>,
+  ldtk_levels: Res<Assets<LdtkLevel>>,
+) {
+  if companions.is_empty() {
+    return;
+  }
+
+  let level_iid = selected_level(&level_selection, level_query.iter(), &ldtk_levels)
+    .map(|ldtk_level| ldtk_level.level.iid.clone());
+
+  for companion_entity in companions.iter() {
+    let mut companion_commands = commands.entity(companion_entity);
+    companion_commands.insert(state_machine());
+    if let Some(level_iid) = &level_iid {
+      companion_commands.insert(InLevel(level_iid.clone()));
+    }
+  }
+}
+
+/// Tells companions the player interacts with to wait there, or to follow again.
+fn command_companions(
+  mut interact_events: EventReader<InteractEvent>,
+  mut companions: Query<(&mut Companion, &mut Interactable, &mut NavPath, &Transform)>,
+) {
+  for &InteractEvent { target, .. } in interact_events.iter() {
+    let Ok((mut companion, mut interactable, mut nav_path, transform)) = companions.get_mut(target)
+    else {
+      continue;
+    };
+
+    (companion.mode, companion.wait_spot) = match companion.mode {
+      CompanionMode::Following => (
+        CompanionMode::Waiting,
+        Some(transform.translation.truncate()),
+      ),
+      CompanionMode::Waiting => (CompanionMode::Following, None),
+    };
+    companion.stuck_for = 0.;
+    interactable.prompt = companion.mode.command().to_string();
+    nav_path.clear();
+  }
+}
+
+/// Makes the player the [`Leader`] of following companions, whenever it spawns, and takes it away
+/// from those told to wait.
+fn update_leaders(
+  mut commands: Commands,
+  companions: Query<(Entity, &Companion, Option<&Leader>)>,
+  players: Query<Entity, With<Player>>,
+) {
+  let player = players.get_single().ok();
+
+  for (companion_entity, companion, leader) in companions.iter() {
+    let leader_entity = player.filter(|_| companion.mode == CompanionMode::Following);
+    if leader.map(|leader| leader.entity) == leader_entity {
+      continue;
+    }
+
+    match leader_entity {
+      Some(entity) => commands.entity(companion_entity).insert(Leader {
+        entity,
+        distance: COMPANION_DISTANCE,
+      }),
+      None => commands.entity(companion_entity).remove::<Leader>(),
+    };
+  }
+}
+
+/// Keeps companions from running off after enemies further than [`COMPANION_FIGHT_RANGE`] from
+/// their leader, or from their wait spot, so a fleeing target can't lure them away.
+fn restrict_targets(
+  mut companions: Query<(&mut Target, &Companion, Option<&Leader>, &GlobalTransform)>,
+  positions: Query<&GlobalTransform>,
+) {
+  for (mut target, companion, leader, transform) in companions.iter_mut() {
+    let Some(target_entity) = target.0 else {
+      continue;
+    };
+
+    let anchor = leader
+      .and_then(|leader| positions.get(leader.entity).ok())
+      .map(|leader_transform| leader_transform.translation().truncate())
+      .or(companion.wait_spot)
+      .unwrap_or_else(|| transform.translation().truncate());
+
+    let in_range = positions
+      .get(target_entity)
+      .map_or(false, |target_transform| {
+        target_transform.translation().truncate().distance(anchor) <= COMPANION_FIGHT_RANGE
+      });
+    if !in_range {
+      target.0 = None;
+    }
+  }
+}
+
+type FollowerGet<'a> = (
+  &'a mut Companion,
+  &'a Leader,
+  &'a Target,
+  &'a mut Transform,
+  &'a mut InLevel,
+  &'a mut NavPath,
+  Option<&'a Offscreen>,
+);
+
+/// Teleports following companions next to their leader when they fall more than
+/// [`TELEPORT_DISTANCE`] behind, are left in another level, or make no progress towards the leader
+/// for [`STUCK_SECONDS`].
+fn catch_up(
+  mut followers: Query<FollowerGet>,
+  leaders: Query<&Transform, Without<Companion>>,
+  grid: Res<WalkabilityGrid>,
+  time: Res<Time>,
+) {
+  let Some(loaded_level) = &grid.level_iid else {
+    return;
+  };
+
+  let delta_seconds = time.delta_seconds();
+  if delta_seconds <= 0. {
+    return;
+  }
+
+  for (mut companion, leader, target, mut transform, mut in_level, mut nav_path, offscreen) in
+    followers.iter_mut()
+  {
+    let Ok(leader_transform) = leaders.get(leader.entity) else {
+      continue;
+    };
+
+    let position = transform.translation.truncate();
+    let leader_position = leader_transform.translation.truncate();
+    let distance = position.distance(leader_position);
+
+    // Standing still while fighting or close enough to the leader isn't being stuck
+    let speed = companion.last_position.map_or(0., |last_position| {
+      position.distance(last_position) / delta_seconds
+    });
+    companion.last_position = Some(position);
+    companion.stuck_for =
+      if target.0.is_none() && distance > leader.distance * 2. && speed < STUCK_SPEED {
+        companion.stuck_for + delta_seconds
+      } else {
+        0.
+      };
+
+    let elsewhere = offscreen.is_some() || in_level.0 != *loaded_level;
+    if !elsewhere && distance <= TELEPORT_DISTANCE && companion.stuck_for < STUCK_SECONDS {
+      continue;
+    }
+
+    // Right next to the leader rather than on top of it, when there's room
+    let leader_tile = grid.tile_at(leader_position);
+    let Some(tile) = grid
+      .successors(&leader_tile)
+      .into_iter()
+      .map(|(tile, _)| tile)
+      .next()
+      .or_else(|| grid.nearest_walkable(&leader_tile))
+    else {
+      continue;
+    };
+
+    transform.translation = grid.tile_center(&tile).extend(transform.translation.z);
+    in_level.0 = loaded_level.clone();
+    nav_path.clear();
+    companion.stuck_for = 0.;
+    companion.last_position = None;
+  }
+}
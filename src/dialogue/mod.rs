@@ -0,0 +1,179 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_asset_loader::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::Deserialize;
+
+use crate::{faction::Faction, inventory::Inventory, utils::ldtk::field_string};
+
+pub mod plugin;
+pub mod screen;
+pub mod systems;
+
+/// Something that must hold for a dialogue choice to be offered.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum Condition {
+  Flag(String),
+  NotFlag(String),
+  HasItem { item: String, amount: u32 },
+  All(Vec<Condition>),
+  Any(Vec<Condition>),
+}
+
+impl Condition {
+  pub fn holds(&self, flags: &HashSet<String>, inventory: Option<&Inventory>) -> bool {
+    match self {
+      Condition::Flag(flag) => flags.contains(flag),
+      Condition::NotFlag(flag) => !flags.contains(flag),
+      Condition::HasItem { item, amount } => {
+        inventory.map_or(false, |inventory| inventory.count(item) >= *amount)
+      }
+      Condition::All(conditions) => conditions
+        .iter()
+        .all(|condition| condition.holds(flags, inventory)),
+      Condition::Any(conditions) => conditions
+        .iter()
+        .any(|condition| condition.holds(flags, inventory)),
+    }
+  }
+}
+
+/// What happens to the world when a line is said or a choice is picked.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum DialogueEffect {
+  SetFlag(String),
+  ClearFlag(String),
+  GiveItem {
+    item: String,
+    amount: u32,
+  },
+  TakeItem {
+    item: String,
+    amount: u32,
+  },
+  /// Puts a quest of `assets/quests/game.quests.ron` in the player's quest log.
+  StartQuest(String),
+  /// Raises or lowers the player's standing with a faction.
+  ChangeReputation {
+    faction: Faction,
+    amount: i32,
+  },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DialogueChoice {
+  pub text: String,
+  /// The node to go to, or the end of the conversation.
+  #[serde(default)]
+  pub next: Option<String>,
+  #[serde(default)]
+  pub condition: Option<Condition>,
+  #[serde(default)]
+  pub effects: Vec<DialogueEffect>,
+}
+
+/// A line of a conversation. Without choices, it continues to `next` or ends the conversation.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DialogueNode {
+  pub speaker: String,
+  /// Image shown next to the line, relative to the assets folder.
+  #[serde(default)]
+  pub portrait: Option<String>,
+  pub text: String,
+  #[serde(default)]
+  pub choices: Vec<DialogueChoice>,
+  #[serde(default)]
+  pub next: Option<String>,
+  /// Applied when the line is shown.
+  #[serde(default)]
+  pub effects: Vec<DialogueEffect>,
+}
+
+impl DialogueNode {
+  /// The choices whose condition holds, with their index among all the choices.
+  pub fn available_choices<'a>(
+    &'a self,
+    flags: &'a HashSet<String>,
+    inventory: Option<&'a Inventory>,
+  ) -> impl Iterator<Item = (usize, &'a DialogueChoice)> {
+    self.choices.iter().enumerate().filter(move |(_, choice)| {
+      choice
+        .condition
+        .as_ref()
+        .map_or(true, |condition| condition.holds(flags, inventory))
+    })
+  }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Conversation {
+  pub start: String,
+  pub nodes: HashMap<String, DialogueNode>,
+}
+
+/// Every conversation of the game keyed by its id, loaded from `assets/dialogue/game.dialogue.ron`.
+#[derive(Clone, Debug, Default, Deserialize, TypeUuid)]
+#[serde(transparent)]
+#[uuid = "9b1e5c3a-7d2f-4e8b-a6c4-1f3d5e7a9b20"]
+pub struct Dialogues(pub HashMap<String, Conversation>);
+
+impl Dialogues {
+  pub fn node(&self, conversation: &str, node: &str) -> Option<&DialogueNode> {
+    self
+      .0
+      .get(conversation)
+      .and_then(|conversation| conversation.nodes.get(node))
+  }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct DialogueAssets {
+  #[asset(path = "dialogue/game.dialogue.ron")]
+  pub dialogues: Handle<Dialogues>,
+}
+
+/// The conversation an NPC starts when the player talks to it, from its `Dialogue` field.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Talker {
+  pub conversation: Option<String>,
+}
+
+impl From<EntityInstance> for Talker {
+  fn from(entity_instance: EntityInstance) -> Talker {
+    Talker {
+      conversation: field_string(&entity_instance, "Dialogue"),
+    }
+  }
+}
+
+/// The conversation being shown, while in `GameState::Dialogue`.
+#[derive(Resource, Clone, Debug)]
+pub struct ActiveDialogue {
+  pub speaker: Entity,
+  pub conversation: String,
+  pub node: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct StartDialogue {
+  pub speaker: Entity,
+  pub conversation: String,
+}
+
+/// Sent when the player leaves a conversation, whichever way.
+#[derive(Clone, Debug)]
+pub struct DialogueEnded {
+  pub speaker: Entity,
+  pub conversation: String,
+}
+
+/// What the player does with the line being shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DialogueInput {
+  /// Go past a line without choices.
+  Continue,
+  /// Pick the choice at this index of the line's choices.
+  Choose(usize),
+  Leave,
+}
@@ -0,0 +1,31 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::{
+  pause::systems::{freeze_gameplay, resume_gameplay},
+  utils::ron_asset::RonAssetAppExt,
+  GameState,
+};
+
+use super::{
+  screen::{despawn_dialogue_screen, spawn_dialogue_screen},
+  DialogueEnded, DialogueInput, Dialogues, StartDialogue,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_ron_asset::<Dialogues>(&["dialogue.ron"])
+      .add_event::<StartDialogue>()
+      .add_event::<DialogueInput>()
+      .add_event::<DialogueEnded>()
+      .add_enter_system(GameState::Dialogue, spawn_dialogue_screen)
+      .add_enter_system(GameState::Dialogue, freeze_gameplay)
+      .add_exit_system(GameState::Dialogue, despawn_dialogue_screen)
+      .add_exit_system(GameState::Dialogue, resume_gameplay)
+      .add_system_set(super::systems::add_systems())
+      .add_system_set(super::screen::add_systems());
+  }
+}
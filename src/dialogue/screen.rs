@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  inventory::Inventory,
+  player::Player,
+  save::WorldProgress,
+  ui::{self, UiAssets},
+  GameState,
+};
+
+use super::{ActiveDialogue, DialogueAssets, DialogueInput, Dialogues};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("dialogue-screen")
+    .with_system(
+      refresh_dialogue_screen
+        .run_in_state(GameState::Dialogue)
+        .label("dialogue-screen-refresh")
+        .after("dialogue-advance"),
+    )
+    .with_system(
+      press_dialogue_buttons
+        .run_in_state(GameState::Dialogue)
+        .label("dialogue-screen-buttons"),
+    )
+}
+
+#[derive(Component)]
+pub struct DialogueScreen;
+
+/// The panel holding the line being shown.
+#[derive(Component)]
+pub struct DialogueBox;
+
+#[derive(Component, Clone, Copy)]
+pub struct DialogueButton(pub DialogueInput);
+
+pub fn spawn_dialogue_screen(mut commands: Commands) {
+  // Keep the world visible above the box
+  let mut overlay = ui::overlay(Color::NONE);
+  overlay.style.justify_content = JustifyContent::FlexEnd;
+  overlay.style.padding = UiRect::all(Val::Px(16.));
+
+  commands
+    .spawn((overlay, DialogueScreen))
+    .with_children(|overlay| {
+      let mut panel = ui::panel();
+      panel.style.flex_direction = FlexDirection::Row;
+      panel.style.size.width = Val::Percent(100.);
+      overlay.spawn((panel, DialogueBox));
+    });
+}
+
+pub fn despawn_dialogue_screen(
+  mut commands: Commands,
+  screens: Query<Entity, With<DialogueScreen>>,
+) {
+  for screen in screens.iter() {
+    commands.entity(screen).despawn_recursive();
+  }
+}
+
+/// Shows the portrait, speaker, text and available choices of the current line whenever it
+/// changes.
+fn refresh_dialogue_screen(
+  mut commands: Commands,
+  boxes: Query<Entity, Added<DialogueBox>>,
+  all_boxes: Query<Entity, With<DialogueBox>>,
+  active_dialogue: Option<Res<ActiveDialogue>>,
+  dialogue_assets: Res<DialogueAssets>,
+  dialogues: Res<Assets<Dialogues>>,
+  world_progress: Res<WorldProgress>,
+  inventories: Query<&Inventory, With<Player>>,
+  ui_assets: Res<UiAssets>,
+  asset_server: Res<AssetServer>,
+) {
+  let Some(active_dialogue) = active_dialogue else {
+    return;
+  };
+
+  let Some(node) = dialogues
+    .get(&dialogue_assets.dialogues)
+    .and_then(|dialogues| dialogues.node(&active_dialogue.conversation, &active_dialogue.node))
+  else {
+    return;
+  };
+
+  let stale_boxes: Vec<Entity> = if active_dialogue.is_changed() || world_progress.is_changed() {
+    all_boxes.iter().collect()
+  } else {
+    boxes.iter().collect()
+  };
+
+  let choices: Vec<(usize, String)> = node
+    .available_choices(&world_progress.flags, inventories.get_single().ok())
+    .map(|(index, choice)| (index, choice.text.clone()))
+    .collect();
+
+  for dialogue_box in stale_boxes {
+    commands.entity(dialogue_box).despawn_descendants();
+    commands.entity(dialogue_box).with_children(|dialogue_box| {
+      if let Some(portrait) = &node.portrait {
+        dialogue_box.spawn(ImageBundle {
+          style: Style {
+            size: Size::new(Val::Px(64.), Val::Px(64.)),
+            margin: UiRect::new(Val::Px(0.), Val::Px(12.), Val::Px(0.), Val::Px(0.)),
+            ..default()
+          },
+          image: asset_server.load(portrait.as_str()).into(),
+          ..default()
+        });
+      }
+
+      dialogue_box
+        .spawn(NodeBundle {
+          style: Style {
+            flex_direction: FlexDirection::Column,
+            flex_grow: 1.,
+            ..default()
+          },
+          ..default()
+        })
+        .with_children(|column| {
+          column.spawn(ui_assets.text(node.speaker.clone(), 22.));
+          column.spawn(ui_assets.text(node.text.clone(), 18.));
+
+          let buttons = if choices.is_empty() {
+            let text = if node.next.is_some() {
+              "Continue"
+            } else {
+              "Close"
+            };
+            vec![(DialogueInput::Continue, text.to_string())]
+          } else {
+            choices
+              .iter()
+              .map(|(index, text)| (DialogueInput::Choose(*index), text.clone()))
+              .collect()
+          };
+
+          for (input, text) in buttons {
+            column
+              .spawn((ui::button(), DialogueButton(input)))
+              .with_children(|button| {
+                button.spawn(ui_assets.text(text, 16.));
+              });
+          }
+        });
+    });
+  }
+}
+
+fn press_dialogue_buttons(
+  buttons: Query<(&Interaction, &DialogueButton), Changed<Interaction>>,
+  mut input_events: EventWriter<DialogueInput>,
+) {
+  for (interaction, &DialogueButton(input)) in buttons.iter() {
+    if *interaction == Interaction::Clicked {
+      input_events.send(input);
+    }
+  }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+  faction::Reputation,
+  interaction::InteractEvent,
+  inventory::{Inventory, ItemAssets, ItemDefinitions},
+  player::{state_machine::TopDownAction, Player},
+  quest::StartQuest,
+  save::WorldProgress,
+  shop::{give_item, Shopkeeper, Wallet},
+  GameState,
+};
+
+use super::{
+  ActiveDialogue, DialogueAssets, DialogueEffect, DialogueEnded, DialogueInput, Dialogues,
+  StartDialogue, Talker,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("dialogue")
+    .with_system(
+      talk_to_npcs
+        .run_in_state(GameState::Playing)
+        .label("dialogue-talk-to-npcs")
+        .after("interaction-interact"),
+    )
+    .with_system(
+      start_dialogue
+        .run_in_state(GameState::Playing)
+        .label("dialogue-start")
+        .after("dialogue-talk-to-npcs"),
+    )
+    .with_system(
+      read_dialogue_input
+        .run_in_state(GameState::Dialogue)
+        .label("dialogue-read-input"),
+    )
+    .with_system(
+      advance_dialogue
+        .run_in_state(GameState::Dialogue)
+        .label("dialogue-advance")
+        .after("dialogue-read-input")
+        .after("dialogue-screen-buttons"),
+    )
+    .with_system(
+      apply_line_effects
+        .run_in_state(GameState::Dialogue)
+        .label("dialogue-apply-line-effects")
+        .after("dialogue-advance"),
+    )
+}
+
+/// Starts the conversation of NPCs the player interacts with. Shopkeepers trade instead.
+fn talk_to_npcs(
+  mut interact_events: EventReader<InteractEvent>,
+  talkers: Query<(&Talker, Option<&Shopkeeper>)>,
+  mut start_events: EventWriter<StartDialogue>,
+) {
+  for &InteractEvent { target, .. } in interact_events.iter() {
+    let Some(conversation) = talkers
+      .get(target)
+      .ok()
+      .filter(|(_, shopkeeper)| shopkeeper.map_or(true, |shopkeeper| shopkeeper.shop.is_none()))
+      .and_then(|(talker, _)| talker.conversation.clone())
+    else {
+      continue;
+    };
+
+    start_events.send(StartDialogue {
+      speaker: target,
+      conversation,
+    });
+  }
+}
+
+fn start_dialogue(
+  mut commands: Commands,
+  mut start_events: EventReader<StartDialogue>,
+  dialogue_assets: Res<DialogueAssets>,
+  dialogues: Res<Assets<Dialogues>>,
+) {
+  let Some(dialogues) = dialogues.get(&dialogue_assets.dialogues) else {
+    return;
+  };
+
+  // Only one conversation can be shown at a time
+  let Some(StartDialogue {
+    speaker,
+    conversation,
+  }) = start_events.iter().last()
+  else {
+    return;
+  };
+
+  let Some(start) = dialogues
+    .0
+    .get(conversation)
+    .map(|conversation| conversation.start.clone())
+  else {
+    warn!("Unknown conversation {conversation:?}");
+    return;
+  };
+
+  commands.insert_resource(ActiveDialogue {
+    speaker: *speaker,
+    conversation: conversation.clone(),
+    node: start,
+  });
+  commands.insert_resource(NextState(GameState::Dialogue));
+}
+
+/// Interact continues past lines without choices, pause leaves the conversation. Choices are
+/// picked with the buttons of the dialogue screen.
+fn read_dialogue_input(
+  players: Query<&ActionState<TopDownAction>, With<Player>>,
+  mut input_events: EventWriter<DialogueInput>,
+) {
+  for action_state in players.iter() {
+    if action_state.just_pressed(TopDownAction::Pause) {
+      input_events.send(DialogueInput::Leave);
+    } else if action_state.just_pressed(TopDownAction::Interact) {
+      input_events.send(DialogueInput::Continue);
+    }
+  }
+}
+
+/// Moves the conversation along, ending it when it runs out of lines.
+fn advance_dialogue(
+  mut commands: Commands,
+  mut input_events: EventReader<DialogueInput>,
+  mut ended_events: EventWriter<DialogueEnded>,
+  active_dialogue: Option<ResMut<ActiveDialogue>>,
+  dialogue_assets: Res<DialogueAssets>,
+  dialogues: Res<Assets<Dialogues>>,
+  mut world_progress: ResMut<WorldProgress>,
+  mut inventories: Query<&mut Inventory, With<Player>>,
+  mut wallets: Query<&mut Wallet, With<Player>>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+  mut quest_events: EventWriter<StartQuest>,
+  mut reputation: ResMut<Reputation>,
+) {
+  let Some(mut active_dialogue) = active_dialogue else {
+    return;
+  };
+
+  let Some(input) = input_events.iter().next() else {
+    return;
+  };
+
+  let node = dialogues
+    .get(&dialogue_assets.dialogues)
+    .and_then(|dialogues| dialogues.node(&active_dialogue.conversation, &active_dialogue.node));
+
+  let next = match (node, input) {
+    (Some(node), DialogueInput::Continue) => {
+      let inventory = inventories.get_single().ok();
+      if node
+        .available_choices(&world_progress.flags, inventory)
+        .next()
+        .is_some()
+      {
+        return;
+      }
+      node.next.clone()
+    }
+    (Some(node), DialogueInput::Choose(choice)) => {
+      let inventory = inventories.get_single().ok();
+      let available = node
+        .available_choices(&world_progress.flags, inventory)
+        .any(|(index, _)| index == *choice);
+      if !available {
+        return;
+      }
+
+      let choice = &node.choices[*choice];
+      apply_effects(
+        &choice.effects,
+        &mut world_progress.flags,
+        inventories.get_single_mut().ok().as_deref_mut(),
+        wallets.get_single_mut().ok().as_deref_mut(),
+        item_definitions.get(&item_assets.definitions),
+        &mut quest_events,
+        &mut reputation,
+      );
+      choice.next.clone()
+    }
+    _ => None,
+  };
+
+  match next {
+    Some(next) => active_dialogue.node = next,
+    None => {
+      ended_events.send(DialogueEnded {
+        speaker: active_dialogue.speaker,
+        conversation: active_dialogue.conversation.clone(),
+      });
+      commands.remove_resource::<ActiveDialogue>();
+      commands.insert_resource(NextState(GameState::Playing));
+    }
+  }
+}
+
+/// Applies the effects of every line as it's shown.
+fn apply_line_effects(
+  active_dialogue: Option<Res<ActiveDialogue>>,
+  dialogue_assets: Res<DialogueAssets>,
+  dialogues: Res<Assets<Dialogues>>,
+  mut world_progress: ResMut<WorldProgress>,
+  mut inventories: Query<&mut Inventory, With<Player>>,
+  mut wallets: Query<&mut Wallet, With<Player>>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+  mut quest_events: EventWriter<StartQuest>,
+  mut reputation: ResMut<Reputation>,
+) {
+  let Some(active_dialogue) =
+    active_dialogue.filter(|active_dialogue| active_dialogue.is_changed())
+  else {
+    return;
+  };
+
+  let Some(node) = dialogues
+    .get(&dialogue_assets.dialogues)
+    .and_then(|dialogues| dialogues.node(&active_dialogue.conversation, &active_dialogue.node))
+  else {
+    return;
+  };
+
+  apply_effects(
+    &node.effects,
+    &mut world_progress.flags,
+    inventories.get_single_mut().ok().as_deref_mut(),
+    wallets.get_single_mut().ok().as_deref_mut(),
+    item_definitions.get(&item_assets.definitions),
+    &mut quest_events,
+    &mut reputation,
+  );
+}
+
+fn apply_effects(
+  effects: &[DialogueEffect],
+  flags: &mut HashSet<String>,
+  mut inventory: Option<&mut Inventory>,
+  mut wallet: Option<&mut Wallet>,
+  definitions: Option<&ItemDefinitions>,
+  quest_events: &mut EventWriter<StartQuest>,
+  reputation: &mut Reputation,
+) {
+  for effect in effects {
+    match effect {
+      DialogueEffect::SetFlag(flag) => {
+        flags.insert(flag.clone());
+      }
+      DialogueEffect::ClearFlag(flag) => {
+        flags.remove(flag);
+      }
+      DialogueEffect::GiveItem { item, amount } => {
+        if let (Some(inventory), Some(definitions)) = (inventory.as_deref_mut(), definitions) {
+          let leftover = give_item(inventory, wallet.as_deref_mut(), definitions, item, *amount);
+          if leftover > 0 {
+            warn!("Inventory full, {leftover} {item} were lost");
+          }
+        }
+      }
+      DialogueEffect::TakeItem { item, amount } => {
+        if let Some(inventory) = inventory.as_deref_mut() {
+          inventory.remove(item, *amount);
+        }
+      }
+      DialogueEffect::StartQuest(quest) => {
+        quest_events.send(StartQuest {
+          quest: quest.clone(),
+        });
+      }
+      DialogueEffect::ChangeReputation { faction, amount } => {
+        reputation.change(*faction, *amount);
+      }
+    }
+  }
+}
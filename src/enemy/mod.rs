@@ -2,7 +2,18 @@ use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::map::ColliderBundle;
+use crate::{
+  animation::{Animator, Facing},
+  bark::Barker,
+  blackboard::Blackboard,
+  combat::{Health, MeleeAttack, Target},
+  faction::Faction,
+  loot::LootTable,
+  map::{ColliderBundle, WallDetection},
+  navigation::NavPath,
+  targeting::Threat,
+  utils::ldtk::field_string,
+};
 
 pub mod plugin;
 pub mod state_machine;
@@ -11,16 +22,52 @@ pub mod systems;
 #[derive(Component, Copy, Clone, Eq, PartialEq, Debug, Default)]
 pub struct Enemy;
 
+/// What kind of enemy this is, from its `Archetype` field (e.g. `skeleton`), for quests to count.
+#[derive(Component, Clone, Debug, Default, Eq, PartialEq)]
+pub struct EnemyArchetype(pub Option<String>);
+
+impl From<EntityInstance> for EnemyArchetype {
+  fn from(entity_instance: EntityInstance) -> EnemyArchetype {
+    EnemyArchetype(field_string(&entity_instance, "Archetype"))
+  }
+}
+
 pub const ENEMY_SPEED: f32 = 300.0;
 
+/// Pixels per second enemies chase at while their level isn't loaded.
+pub const OFFSCREEN_SPEED: f32 = 30.0;
+
+/// How many levels away the player can get before a chasing enemy gives up.
+pub const PURSUIT_LEVELS: usize = 2;
+
+/// Seconds after which what an enemy saw or heard is no longer worth investigating.
+pub const LEAD_MAX_AGE: f32 = 10.;
+
 #[derive(Default, Bundle, LdtkEntity)]
 pub struct EnemyBundle {
   #[from_entity_instance]
   #[bundle]
   pub collider_bundle: ColliderBundle,
+  pub wall_detection: WallDetection,
+  pub animator: Animator,
+  pub facing: Facing,
 
   pub enemy: Enemy,
+  #[from_entity_instance]
+  pub archetype: EnemyArchetype,
+  #[from_entity_instance]
+  pub faction: Faction,
   pub controller: KinematicCharacterController,
+  pub health: Health,
+  pub target: Target,
+  pub threat: Threat,
+  pub blackboard: Blackboard,
+  pub barker: Barker,
+  pub melee_attack: MeleeAttack,
+  pub nav_path: NavPath,
+
+  #[from_entity_instance]
+  pub loot_table: LootTable,
 
   #[worldly]
   pub worldly: Worldly,
@@ -1,29 +1,129 @@
 use bevy::prelude::*;
 use seldom_state::prelude::Trigger;
 
+use crate::{
+  blackboard::Blackboard,
+  combat::Target,
+  navigation::{InLevel, LevelGraph, WalkabilityGrid},
+};
+
+/// The distance from `entity` to its [`Target`], if it has one.
+fn distance_to_target(
+  entity: Entity,
+  targets: &Query<&'static Target>,
+  transforms: &Query<&'static Transform>,
+) -> Option<f32> {
+  let Target(Some(target)) = targets.get(entity).ok()? else {
+    return None;
+  };
+
+  let delta = transforms.get(*target).ok()?.translation - transforms.get(entity).ok()?.translation;
+  Some(delta.length())
+}
+
+/// Holds while the entity's [`Target`] is within `range`.
 #[derive(Clone, Copy, FromReflect, Reflect)]
 pub struct Near {
-  target: Entity,
   range: f32,
 }
 
 impl Near {
-  pub fn new(target: Entity, range: f32) -> Self {
-    Self { target, range }
+  pub fn new(range: f32) -> Self {
+    Self { range }
   }
 }
 
 impl Trigger for Near {
-  // mut transforms: Query<(&mut Transform, &Handle<ColorMaterial>)>,
-  type Param<'w, 's> = (Query<'w, 's, &'static Transform>, Res<'s, Time>);
+  type Param<'w, 's> = (
+    Query<'w, 's, &'static Target>,
+    Query<'w, 's, &'static Transform>,
+    Query<'w, 's, &'static InLevel>,
+    Res<'w, WalkabilityGrid>,
+  );
+
+  fn trigger(
+    &self,
+    entity: Entity,
+    (targets, transforms, levels, grid): &Self::Param<'_, '_>,
+  ) -> bool {
+    // Every level spawns at the origin, so only entities of the loaded level can be compared
+    if let Ok(InLevel(level_iid)) = levels.get(entity) {
+      if grid.level_iid.as_ref() != Some(level_iid) {
+        return false;
+      }
+    }
+
+    distance_to_target(entity, targets, transforms).map_or(false, |distance| distance < self.range)
+  }
+}
+
+/// Holds while the entity's [`Target`] is within `range` in the same level, or at most
+/// `max_levels` levels away from this entity's level, so a chase can continue into neighbouring
+/// levels.
+#[derive(Clone, Copy, FromReflect, Reflect)]
+pub struct Pursuing {
+  range: f32,
+  max_levels: usize,
+}
+
+impl Pursuing {
+  pub fn new(range: f32, max_levels: usize) -> Self {
+    Self { range, max_levels }
+  }
+}
+
+impl Trigger for Pursuing {
+  type Param<'w, 's> = (
+    Query<'w, 's, &'static Target>,
+    Query<'w, 's, &'static Transform>,
+    Query<'w, 's, &'static InLevel>,
+    Res<'w, WalkabilityGrid>,
+    Res<'w, LevelGraph>,
+  );
+
+  fn trigger(
+    &self,
+    entity: Entity,
+    (targets, transforms, levels, grid, level_graph): &Self::Param<'_, '_>,
+  ) -> bool {
+    if !matches!(targets.get(entity), Ok(Target(Some(_)))) {
+      return false;
+    }
 
-  fn trigger(&self, entity: Entity, (transforms, _time): &Self::Param<'_, '_>) -> bool {
-    // Find the displacement between the target and this entity
-    let delta = transforms.get(self.target).unwrap().translation
-      - transforms.get(entity).unwrap().translation;
+    // Targets are only picked in the loaded level
+    match (levels.get(entity), &grid.level_iid) {
+      (Ok(InLevel(level_iid)), Some(target_level_iid)) if level_iid != target_level_iid => {
+        level_graph
+          .route(level_iid, target_level_iid)
+          .map(|route| route.len() - 1 <= self.max_levels)
+          .unwrap_or(false)
+      }
+      _ => distance_to_target(entity, targets, transforms)
+        .map_or(false, |distance| distance < self.range),
+    }
+  }
+}
+
+/// Holds while the entity's [`Blackboard`] has a lead worth investigating, no older than `max_age`
+/// seconds.
+#[derive(Clone, Copy, FromReflect, Reflect)]
+pub struct HasLead {
+  max_age: f32,
+}
+
+impl HasLead {
+  pub fn new(max_age: f32) -> Self {
+    Self { max_age }
+  }
+}
+
+impl Trigger for HasLead {
+  type Param<'w, 's> = Query<'w, 's, &'static Blackboard>;
 
-    // Check if the distance is less than the range
-    delta.length() < self.range
+  fn trigger(&self, entity: Entity, blackboards: &Self::Param<'_, '_>) -> bool {
+    blackboards
+      .get(entity)
+      .map_or(false, |blackboard| blackboard.lead(self.max_age).is_some())
   }
 }
 
@@ -32,16 +132,30 @@ impl Trigger for Near {
 #[component(storage = "SparseSet")]
 pub struct Idle;
 
-// Entities is the `Follow` state should move towards the given entity at the given speed
+// Entities is the `Follow` state should move towards their `Target` at the given speed
 #[derive(Clone, Component, Reflect)]
 #[component(storage = "SparseSet")]
 pub struct Follow {
-  pub target: Entity,
   pub speed: f32,
 }
 
 impl Follow {
-  pub fn new(target: Entity, speed: f32) -> Self {
-    Self { target, speed }
+  pub fn new(speed: f32) -> Self {
+    Self { speed }
+  }
+}
+
+// Entities in the `Investigate` state should walk to the freshest lead on their `Blackboard`, no
+// older than `max_age` seconds, and cross it off once there
+#[derive(Clone, Component, Reflect)]
+#[component(storage = "SparseSet")]
+pub struct Investigate {
+  pub speed: f32,
+  pub max_age: f32,
+}
+
+impl Investigate {
+  pub fn new(speed: f32, max_age: f32) -> Self {
+    Self { speed, max_age }
   }
 }
@@ -1,17 +1,24 @@
-use astar_pathfinding::astar;
 use bevy::prelude::*;
-use bevy_ecs_ldtk::{
-  prelude::*,
-  utils::{grid_coords_to_translation, translation_to_grid_coords},
-};
+use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::IntoConditionalSystem;
 use seldom_state::prelude::*;
 
-use crate::{player::Player, utils::position::Pos};
+use crate::{
+  blackboard::Blackboard,
+  combat::Target,
+  companion::Leader,
+  map::{WallDetection, WallDirection},
+  navigation::{InLevel, LevelGraph, NavPath, Offscreen, WalkabilityGrid},
+  player::Player,
+  targeting::PERCEPTION_RANGE,
+  utils::{ldtk::selected_level, position::Pos},
+  GameState,
+};
 
 use super::{
-  state_machine::{Follow, Idle, Near},
-  Enemy,
+  state_machine::{Follow, HasLead, Idle, Investigate, Near, Pursuing},
+  Enemy, LEAD_MAX_AGE, OFFSCREEN_SPEED, PURSUIT_LEVELS,
 };
 
 pub fn add_systems() -> SystemSet {
@@ -23,7 +30,22 @@ pub fn add_systems() -> SystemSet {
         .after("player")
         .after("player-spawn"),
     )
-    .with_system(follow.label("enemy-follow").after("enemy-spawn"))
+    .with_system(
+      follow
+        .run_in_state(GameState::Playing)
+        .label("enemy-follow")
+        .after("enemy-spawn")
+        .after("navigation-offscreen")
+        .after("navigation-invalidate-paths"),
+    )
+    .with_system(
+      investigate
+        .run_in_state(GameState::Playing)
+        .label("enemy-investigate")
+        .after("enemy-spawn")
+        .after("navigation-invalidate-paths")
+        .after("blackboard"),
+    )
     .with_system(
       update_grid_coords_from_enemy
         .label("enemy-grid-coords")
@@ -36,37 +58,64 @@ pub fn add_systems() -> SystemSet {
   // )
 }
 
-type PlayerGet<'a> = Entity;
-type PlayerWhen = (Added<EntityInstance>, With<Player>, Without<Enemy>);
-
-type EnemyGet<'a> = Entity;
-type EnemyWhen = (Added<EntityInstance>, With<Enemy>, Without<Player>);
+type EnemyGet<'a> = (Entity, Option<&'a InLevel>);
+type EnemyWhen = (With<Enemy>, Without<StateMachine>, Without<Player>);
 
-/// When the player is added through the ldtk bundle and the enemy is added through the ldtk bundle,
-/// this system will add a follow component to the enemy and a state machine component to the enemy.
+/// Gives every enemy without one a state machine chasing its [`Target`], however and whenever it
+/// was spawned, and remembers the level it was spawned in.
 pub fn spawn(
   mut commands: Commands,
-  players: Query<PlayerGet, PlayerWhen>,
   enemies: Query<EnemyGet, EnemyWhen>,
+  level_selection: Res<LevelSelection>,
+  level_query: Query<&Handle<LdtkLevel>>,
+  ldtk_levels: Res<Assets<LdtkLevel>>,
 ) {
-  for player_entity in players.iter() {
-    for enemy_entity in enemies.iter() {
-      let follow_speed = 100.;
-      let follow_distance = 300.;
-
-      let near_player = Near::new(player_entity, follow_distance);
-
-      commands
-        .entity(enemy_entity)
-        .insert((StateMachine::new(Idle)
-          // Idle --(near_player)-> Follow
-          .trans::<Idle>(near_player, Follow::new(player_entity, follow_speed))
-          // Follow --(!near_player)-> Idle
-          .trans::<Follow>(NotTrigger(near_player), Idle),));
+  // Enemies are worldly, so they only spawn with the level they are authored in
+  let level_iid = selected_level(&level_selection, level_query.iter(), &ldtk_levels)
+    .map(|ldtk_level| ldtk_level.level.iid.clone());
+
+  for (enemy_entity, in_level) in enemies.iter() {
+    let mut enemy_commands = commands.entity(enemy_entity);
+    enemy_commands.insert((state_machine(false),));
+    if let (None, Some(level_iid)) = (in_level, &level_iid) {
+      enemy_commands.insert(InLevel(level_iid.clone()));
     }
   }
 }
 
+/// Builds the state machine of an enemy chasing its [`Target`], and investigating what's on its
+/// [`Blackboard`] once it loses it, starting in `Follow` instead of `Idle` when `following` (e.g.
+/// when restoring a saved game).
+pub fn state_machine(following: bool) -> StateMachine {
+  let follow_speed = 30.;
+  let follow_distance = PERCEPTION_RANGE;
+  let investigate_speed = 20.;
+
+  let near_target = Near::new(follow_distance);
+  let pursuing_target = Pursuing::new(follow_distance, PURSUIT_LEVELS);
+  let has_lead = HasLead::new(LEAD_MAX_AGE);
+  let follow = Follow::new(follow_speed);
+  let investigate = Investigate::new(investigate_speed, LEAD_MAX_AGE);
+
+  let state_machine = if following {
+    StateMachine::new(follow.clone())
+  } else {
+    StateMachine::new(Idle)
+  };
+
+  state_machine
+    // Idle --(near_target)-> Follow
+    .trans::<Idle>(near_target, follow.clone())
+    // Idle --(has_lead)-> Investigate
+    .trans::<Idle>(has_lead, investigate.clone())
+    // Follow --(!pursuing_target)-> Investigate
+    .trans::<Follow>(NotTrigger(pursuing_target), investigate)
+    // Investigate --(near_target)-> Follow
+    .trans::<Investigate>(near_target, follow)
+    // Investigate --(!has_lead)-> Idle
+    .trans::<Investigate>(NotTrigger(has_lead), Idle)
+}
+
 /// When the enemy has a follow component, this system will move the enemy towards the target.
 /// This function runs every tick.
 // fn follow(
@@ -110,120 +159,177 @@ pub fn spawn(
 //   }
 // }
 
-/// When the enemy has a follow component, this system will move the enemy towards the target using
-/// A* pathfinding. This function runs every tick.
+/// When the enemy has a follow component, this system will move the enemy towards its [`Target`]
+/// using A* pathfinding, planning again only when its [`NavPath`] no longer leads to the target.
+/// Followers without a target, like companions, go after their [`Leader`] instead and stop once
+/// close enough. Enemies outside of the loaded level use [`follow_offscreen`] instead. This
+/// function runs every tick.
 fn follow(
-  follows: Query<(Entity, &Follow), With<Enemy>>,
-  mut movable_entities: Query<
-    (&mut KinematicCharacterController, &GridCoords, &Transform),
-    With<Velocity>,
-  >,
-  level_query: Query<&Handle<LdtkLevel>>,
-  ldtk_levels: Res<Assets<LdtkLevel>>,
-  level_selection: Res<LevelSelection>,
+  mut follows: Query<(
+    Entity,
+    &Follow,
+    &Target,
+    Option<&Leader>,
+    &mut InLevel,
+    &mut NavPath,
+    &WallDetection,
+    Option<&Offscreen>,
+  )>,
+  mut movable_entities: Query<(&mut KinematicCharacterController, &mut Transform), With<Velocity>>,
+  grid: Res<WalkabilityGrid>,
+  level_graph: Res<LevelGraph>,
   time: Res<Time>,
 ) {
-  if follows.iter().count() == 0 {
+  let Some(loaded_level) = &grid.level_iid else {
     return;
-  }
+  };
+
+  for (
+    enemy_entity,
+    follow,
+    &Target(target),
+    leader,
+    mut in_level,
+    mut nav_path,
+    wall_detection,
+    offscreen,
+  ) in follows.iter_mut()
+  {
+    let (target_entity, keep_distance) = match (target, leader) {
+      (Some(target_entity), _) => (target_entity, 0.),
+      (None, Some(leader)) => (leader.entity, leader.distance),
+      (None, None) => continue,
+    };
+
+    let Ok([
+      (mut enemy_controller, mut enemy_transform),
+      (_, target_transform),
+    ]) = movable_entities.get_many_mut([enemy_entity, target_entity]) else {
+      continue;
+    };
 
-  for level_handle in level_query.iter() {
-    let level = &ldtk_levels
-      .get(level_handle)
-      .expect("Level not found")
-      .level;
+    if offscreen.is_some() || in_level.0 != *loaded_level {
+      enemy_controller.translation = None;
+      nav_path.clear();
+      follow_offscreen(
+        &level_graph,
+        loaded_level,
+        &mut in_level,
+        &mut enemy_transform,
+        OFFSCREEN_SPEED * time.delta_seconds(),
+      );
+      continue;
+    }
 
-    if !level_selection.is_match(&0, level) {
+    let enemy_position = enemy_transform.translation.truncate();
+    let target_position = target_transform.translation.truncate();
+    if enemy_position.distance(target_position) <= keep_distance {
       continue;
     }
 
-    let mut walls: Vec<Pos> = Vec::new();
+    let enemy_grid_position = grid.tile_at(enemy_position);
+    let target_grid_position = grid.tile_at(target_position);
 
-    for layer_instance in level
-      .layer_instances
-      .as_ref()
-      .expect("No layer instance")
-      .iter()
+    if !nav_path.leads_to(&grid, &enemy_grid_position, &target_grid_position) {
+      nav_path.plan(&grid, &enemy_grid_position, &target_grid_position);
+    }
+
+    // Find the next position, after the enemy current position
+    let Some(next_tile) = nav_path.advance(&enemy_grid_position) else {
+      continue;
+    };
+
+    // Steer the enemy towards the target
+    let next_position = grid.tile_center(next_tile);
+
+    let desired_translation = (next_position - enemy_position).normalize_or_zero()
+      * time.delta_seconds()
+      * follow.speed;
+
+    // Blocked on the way to the next tile, plan again from wherever the enemy ends up
+    if WallDirection::from_translation(desired_translation)
+      .any(|direction| wall_detection.is_touching_wall(direction))
     {
-      let LayerInstance {
-        identifier,
-        auto_layer_tiles,
-        ..
-      } = layer_instance;
-
-      walls = if identifier == "Walls" {
-        auto_layer_tiles
-          .iter()
-          .map(|grid_tile| Pos(grid_tile.px[0] / 16, grid_tile.px[1] / 16))
-          .collect()
-      } else {
-        walls
-      }
+      nav_path.clear();
     }
 
-    let (
-      enemy_entity,
-      &Follow {
-        target: player_entity,
-        ..
-      },
-    ) = follows.single();
-
-    let [
-      (
-        mut enemy_controller,
-        enemy_grid_position,
-        &Transform {
-          translation: enemy_position,
-          ..
-        },
-      ),
-      (_, player_grid_position, _),
-    ] = movable_entities
-      .get_many_mut([enemy_entity, player_entity])
-      .unwrap();
-
-    let enemy_grid_position = Pos(enemy_grid_position.x, enemy_grid_position.y);
-    let player_grid_position = Pos(player_grid_position.x, player_grid_position.y);
-
-    let path = astar(
-      &enemy_grid_position,
-      |p| p.successors(&walls),
-      |p| p.manhattan_distance(&player_grid_position),
-      |p| *p == player_grid_position,
-    );
-
-    if path.is_none() {
-      return;
+    enemy_controller.translation = match enemy_controller.translation {
+      Some(translation) => Some(translation + desired_translation),
+      None => Some(desired_translation),
+    };
+  }
+}
+
+type InvestigatorGet<'a> = (
+  &'a Investigate,
+  &'a mut Blackboard,
+  &'a mut NavPath,
+  &'a mut KinematicCharacterController,
+  &'a Transform,
+  &'a WallDetection,
+  Option<&'a InLevel>,
+);
+
+/// When the enemy has an investigate component, this system will move the enemy towards the
+/// freshest lead on its [`Blackboard`] using A* pathfinding, and forget the lead once it stands on
+/// it or finds no way there. Enemies outside of the loaded level wait for their leads to go stale.
+fn investigate(
+  mut investigators: Query<InvestigatorGet, (With<Enemy>, Without<Offscreen>)>,
+  grid: Res<WalkabilityGrid>,
+  time: Res<Time>,
+) {
+  let Some(loaded_level) = &grid.level_iid else {
+    return;
+  };
+
+  for (
+    investigate,
+    mut blackboard,
+    mut nav_path,
+    mut enemy_controller,
+    enemy_transform,
+    wall_detection,
+    in_level,
+  ) in investigators.iter_mut()
+  {
+    if in_level.map_or(false, |InLevel(level_iid)| level_iid != loaded_level) {
+      continue;
     }
 
-    let path = path
-      .unwrap()
-      .0
-      .iter()
-      .map(|p| Pos(p.0, p.1))
-      .collect::<Vec<Pos>>();
-
-    // Find the next position. Find the index of the enemy current position and get the next one,
-    // if the enemy is not in the path, get the first position.
-    let next_tile_index = path
-      .iter()
-      .position(|p| *p == enemy_grid_position)
-      .unwrap_or(0)
-      + 1;
-    let path = path.split_at(next_tile_index).1;
-
-    let next_tile = GridCoords {
-      x: path[0].0,
-      y: 16 - path[0].1 - 1,
+    let Some((lead, lead_position)) = blackboard.lead(investigate.max_age) else {
+      continue;
     };
 
-    // Steer the enemy towards the target
-    let target_position = grid_coords_to_translation(next_tile, (16, 16).into());
+    let enemy_position = enemy_transform.translation.truncate();
+    let enemy_grid_position = grid.tile_at(enemy_position);
+    let lead_grid_position = grid.tile_at(lead_position);
+
+    if enemy_grid_position == lead_grid_position {
+      blackboard.forget(&lead);
+      nav_path.clear();
+      continue;
+    }
+
+    if !nav_path.leads_to(&grid, &enemy_grid_position, &lead_grid_position) {
+      nav_path.plan(&grid, &enemy_grid_position, &lead_grid_position);
+    }
 
-    let desired_translation = (target_position - enemy_position.truncate()).normalize_or_zero()
+    // Nothing more to learn about a place that can't be reached
+    let Some(next_tile) = nav_path.advance(&enemy_grid_position) else {
+      blackboard.forget(&lead);
+      continue;
+    };
+
+    let desired_translation = (grid.tile_center(next_tile) - enemy_position).normalize_or_zero()
       * time.delta_seconds()
-      * 30.;
+      * investigate.speed;
+
+    // Blocked on the way to the next tile, plan again from wherever the enemy ends up
+    if WallDirection::from_translation(desired_translation)
+      .any(|direction| wall_detection.is_touching_wall(direction))
+    {
+      nav_path.clear();
+    }
 
     enemy_controller.translation = match enemy_controller.translation {
       Some(translation) => Some(translation + desired_translation),
@@ -232,14 +338,55 @@ fn follow(
   }
 }
 
+/// Coarse pursuit for enemies whose level isn't loaded: they walk in a straight line to the border
+/// of the next level on the way to the player's and cross it, ignoring walls. Once they reach the
+/// loaded level they are placed on a walkable tile and path find as usual.
+fn follow_offscreen(
+  level_graph: &LevelGraph,
+  loaded_level: &str,
+  in_level: &mut InLevel,
+  transform: &mut Transform,
+  step: f32,
+) {
+  let Some(route) = level_graph.route(&in_level.0, loaded_level) else {
+    return;
+  };
+  let Some((exit, entry)) = route
+    .get(1)
+    .and_then(|next_level| level_graph.crossing(&in_level.0, next_level))
+  else {
+    return;
+  };
+
+  let position = transform.translation.truncate();
+  let z = transform.translation.z;
+
+  if position.distance(exit) <= step {
+    transform.translation = entry.extend(z);
+    in_level.0 = route[1].clone();
+  } else {
+    transform.translation = (position + (exit - position).normalize() * step).extend(z);
+  }
+}
+
+/// Keeps the [`GridCoords`] of enemies in the loaded level on their tile, counted from the top like
+/// the [`WalkabilityGrid`].
 fn update_grid_coords_from_enemy(
-  mut player: Query<(&Transform, &mut GridCoords), (With<Enemy>, Without<Player>)>,
+  mut player: Query<
+    (&Transform, &mut GridCoords),
+    (With<Enemy>, Without<Player>, Without<Offscreen>),
+  >,
+  grid: Res<WalkabilityGrid>,
 ) {
+  if grid.level_iid.is_none() {
+    return;
+  }
+
   for (transform, mut grid_coords) in player.iter_mut() {
-    let grid = translation_to_grid_coords(transform.translation.truncate(), (16, 16).into());
+    let Pos(x, y) = grid.tile_at(transform.translation.truncate());
 
-    grid_coords.x = grid.x;
-    grid_coords.y = 16 - grid.y - 1;
+    grid_coords.x = x;
+    grid_coords.y = y;
   }
 }
 
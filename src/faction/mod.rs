@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::ldtk::field_string;
+
+pub mod plugin;
+pub mod systems;
+
+pub const MIN_REPUTATION: i32 = -100;
+pub const MAX_REPUTATION: i32 = 100;
+
+/// At or below this standing a faction is hostile to the player whatever the relation matrix says.
+pub const HOSTILE_REPUTATION: i32 = -50;
+
+/// At or above this standing a faction is friendly to the player whatever the relation matrix says.
+pub const FRIENDLY_REPUTATION: i32 = 50;
+
+/// Standing lost with a faction for each hit the player lands on one of its members.
+pub const HIT_REPUTATION: i32 = -5;
+
+/// Standing lost with a faction for each of its members the player kills. Factions hostile to the
+/// victim's, but not to the player, gain half of it.
+pub const KILL_REPUTATION: i32 = -30;
+
+/// Who a character sides with, from its `Faction` field or else its LDtk identifier.
+#[derive(
+  Component,
+  Clone,
+  Copy,
+  Debug,
+  Default,
+  Deserialize,
+  Eq,
+  Hash,
+  Ord,
+  PartialEq,
+  PartialOrd,
+  Serialize,
+)]
+pub enum Faction {
+  Player,
+  #[default]
+  Monsters,
+  /// Knights keeping the peace.
+  Guards,
+  /// Thieves preying on everyone else.
+  Bandits,
+  Villagers,
+}
+
+impl Faction {
+  pub const ALL: [Faction; 5] = [
+    Faction::Player,
+    Faction::Monsters,
+    Faction::Guards,
+    Faction::Bandits,
+    Faction::Villagers,
+  ];
+}
+
+impl std::str::FromStr for Faction {
+  type Err = String;
+
+  fn from_str(faction: &str) -> Result<Self, Self::Err> {
+    Faction::ALL
+      .into_iter()
+      .find(|candidate| format!("{candidate:?}").eq_ignore_ascii_case(faction.trim()))
+      .ok_or_else(|| format!("unknown faction {faction:?}"))
+  }
+}
+
+impl From<EntityInstance> for Faction {
+  fn from(entity_instance: EntityInstance) -> Faction {
+    if let Some(faction) = field_string(&entity_instance, "Faction") {
+      match faction.parse() {
+        Ok(faction) => return faction,
+        Err(error) => warn!("Invalid faction of {}: {error}", entity_instance.identifier),
+      }
+    }
+
+    match entity_instance.identifier.as_ref() {
+      "Player" | "Companion" => Faction::Player,
+      "Guard" => Faction::Guards,
+      "Bandit" => Faction::Bandits,
+      "Npc" => Faction::Villagers,
+      _ => Faction::Monsters,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Relation {
+  Hostile,
+  Neutral,
+  Friendly,
+}
+
+/// How factions feel about each other, both ways. Members of a faction are always friendly to each
+/// other and pairs left out are neutral.
+#[derive(Resource, Clone, Debug)]
+pub struct FactionRelations(pub HashMap<(Faction, Faction), Relation>);
+
+impl Default for FactionRelations {
+  fn default() -> Self {
+    use Faction::*;
+    use Relation::*;
+
+    Self(HashMap::from([
+      ((Monsters, Player), Hostile),
+      ((Monsters, Guards), Hostile),
+      ((Monsters, Bandits), Hostile),
+      ((Monsters, Villagers), Hostile),
+      ((Guards, Player), Neutral),
+      ((Guards, Bandits), Hostile),
+      ((Guards, Villagers), Friendly),
+      ((Bandits, Player), Hostile),
+      ((Bandits, Villagers), Hostile),
+      ((Villagers, Player), Friendly),
+    ]))
+  }
+}
+
+impl FactionRelations {
+  /// The relation from the matrix alone.
+  pub fn base(&self, a: Faction, b: Faction) -> Relation {
+    if a == b {
+      return Relation::Friendly;
+    }
+
+    self
+      .0
+      .get(&(a, b))
+      .or_else(|| self.0.get(&(b, a)))
+      .copied()
+      .unwrap_or(Relation::Neutral)
+  }
+
+  /// The relation between two factions, with the player's [`Reputation`] overriding the matrix
+  /// once it's high or low enough.
+  pub fn between(&self, reputation: &Reputation, a: Faction, b: Faction) -> Relation {
+    let other = match (a, b) {
+      (Faction::Player, other) | (other, Faction::Player) if other != Faction::Player => other,
+      _ => return self.base(a, b),
+    };
+
+    match reputation.get(other) {
+      standing if standing <= HOSTILE_REPUTATION => Relation::Hostile,
+      standing if standing >= FRIENDLY_REPUTATION => Relation::Friendly,
+      _ => self.base(a, b),
+    }
+  }
+
+  pub fn is_hostile(&self, reputation: &Reputation, a: Faction, b: Faction) -> bool {
+    self.between(reputation, a, b) == Relation::Hostile
+  }
+}
+
+/// The player's standing with each faction, from [`MIN_REPUTATION`] to [`MAX_REPUTATION`]. Saved
+/// along with the game.
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Reputation(pub HashMap<Faction, i32>);
+
+impl Reputation {
+  pub fn get(&self, faction: Faction) -> i32 {
+    self.0.get(&faction).copied().unwrap_or_default()
+  }
+
+  pub fn change(&mut self, faction: Faction, amount: i32) {
+    if faction == Faction::Player || amount == 0 {
+      return;
+    }
+
+    let standing = self.0.entry(faction).or_default();
+    *standing = (*standing + amount).clamp(MIN_REPUTATION, MAX_REPUTATION);
+  }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::{systems::reset_reputation, FactionRelations, Reputation};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<FactionRelations>()
+      .init_resource::<Reputation>()
+      .add_enter_system(GameState::MainMenu, reset_reputation)
+      .add_exit_system(GameState::GameOver, reset_reputation)
+      .add_exit_system(GameState::Victory, reset_reputation)
+      .add_system_set(super::systems::add_systems());
+  }
+}
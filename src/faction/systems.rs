@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  combat::{DamageEvent, DeathEvent},
+  player::Player,
+  GameState,
+};
+
+use super::{Faction, FactionRelations, Relation, Reputation, HIT_REPUTATION, KILL_REPUTATION};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new().label("faction").with_system(
+    track_reputation
+      .run_in_state(GameState::Playing)
+      .label("faction-track-reputation")
+      .after("combat-apply-damage"),
+  )
+}
+
+pub fn reset_reputation(mut reputation: ResMut<Reputation>) {
+  *reputation = Reputation::default();
+}
+
+/// Lowers the player's standing with the factions it hurts and kills, and raises it on a kill with
+/// their enemies that aren't hostile to the player.
+fn track_reputation(
+  mut damage_events: EventReader<DamageEvent>,
+  mut death_events: EventReader<DeathEvent>,
+  mut reputation: ResMut<Reputation>,
+  relations: Res<FactionRelations>,
+  players: Query<(), With<Player>>,
+  factions: Query<&Faction>,
+) {
+  let by_player = |source: Option<Entity>| source.map_or(false, |source| players.contains(source));
+
+  // Damage dealt a little every frame, like spikes, doesn't count as a hit
+  let hits: Vec<Faction> = damage_events
+    .iter()
+    .filter(|damage_event| damage_event.amount >= 1. && by_player(damage_event.source))
+    .filter_map(|damage_event| factions.get(damage_event.target).ok().copied())
+    .collect();
+  let kills: Vec<Faction> = death_events
+    .iter()
+    .filter(|death_event| by_player(death_event.killer))
+    .filter_map(|death_event| factions.get(death_event.entity).ok().copied())
+    .collect();
+
+  for faction in hits {
+    reputation.change(faction, HIT_REPUTATION);
+  }
+
+  for victim in kills {
+    reputation.change(victim, KILL_REPUTATION);
+
+    // Factions at odds with the player don't warm up to them for thinning out their rivals
+    for faction in Faction::ALL {
+      if relations.base(faction, victim) == Relation::Hostile
+        && relations.base(faction, Faction::Player) != Relation::Hostile
+      {
+        reputation.change(faction, -KILL_REPUTATION / 2);
+      }
+    }
+  }
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{companion::CompanionMode, utils::ldtk::*};
+
+pub mod plugin;
+pub mod systems;
+
+/// How far off the player's facing an interactable can be and still get the focus, as the cosine
+/// of the angle between the two (about 70 degrees either side).
+pub const FOCUS_MIN_ALIGNMENT: f32 = 0.35;
+
+/// Closer than this, interactables get the focus whichever way the player looks, in pixels.
+pub const FOCUS_TOUCH_DISTANCE: f32 = 6.;
+
+/// Something the player can use by pressing interact within `radius` pixels of it. When several
+/// are in range, the highest `priority` wins, then the closest.
+#[derive(Component, Clone, Debug)]
+pub struct Interactable {
+  pub radius: f32,
+  pub priority: i32,
+  /// The verb shown in the prompt, e.g. "Talk" or "Open".
+  pub prompt: String,
+}
+
+impl Default for Interactable {
+  fn default() -> Self {
+    Self {
+      radius: 16.,
+      priority: 0,
+      prompt: "Interact".into(),
+    }
+  }
+}
+
+impl From<EntityInstance> for Interactable {
+  fn from(entity_instance: EntityInstance) -> Interactable {
+    let (radius, priority, prompt) = match entity_instance.identifier.as_ref() {
+      "Npc" if field_string(&entity_instance, "Shop").is_some() => (24., 2, "Trade"),
+      "Npc" => (24., 2, "Talk"),
+      "Chest" => (20., 1, "Open"),
+      "Door" => (16., 1, "Enter"),
+      "Lever" => (16., 1, "Pull"),
+      "Companion" => (20., 2, CompanionMode::default().command()),
+      _ => {
+        let default = Interactable::default();
+        (default.radius, default.priority, "Interact")
+      }
+    };
+
+    Interactable {
+      radius: field_float(&entity_instance, "InteractRadius").unwrap_or(radius),
+      priority,
+      prompt: field_string(&entity_instance, "Prompt").unwrap_or_else(|| prompt.to_string()),
+    }
+  }
+}
+
+/// The interactable the player would use by pressing interact right now, if any.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct InteractionFocus(pub Option<Entity>);
+
+/// Sent when `interactor` uses `target`. Each kind of interactable reacts to it on its own.
+#[derive(Clone, Copy, Debug)]
+pub struct InteractEvent {
+  pub interactor: Entity,
+  pub target: Entity,
+}
+
+/// The text telling the player what interact would do.
+#[derive(Component)]
+pub struct InteractionPrompt;
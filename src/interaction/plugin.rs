@@ -0,0 +1,22 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::{
+  systems::{despawn_interaction_prompt, spawn_interaction_prompt},
+  InteractEvent, InteractionFocus,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<InteractionFocus>()
+      .add_event::<InteractEvent>()
+      .add_enter_system(GameState::Playing, spawn_interaction_prompt)
+      .add_exit_system(GameState::Playing, despawn_interaction_prompt)
+      .add_system_set(super::systems::add_systems());
+  }
+}
@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+
+use crate::{
+  animation::Facing,
+  combat::Dead,
+  navigation::Offscreen,
+  player::{
+    controller::{binding_label, MyGamepad},
+    state_machine::TopDownAction,
+    Player,
+  },
+  ui::UiAssets,
+  GameState,
+};
+
+use super::{
+  InteractEvent, Interactable, InteractionFocus, InteractionPrompt, FOCUS_MIN_ALIGNMENT,
+  FOCUS_TOUCH_DISTANCE,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("interaction")
+    .with_system(
+      update_interaction_focus
+        .run_in_state(GameState::Playing)
+        .label("interaction-focus")
+        .after("animation-select-states"),
+    )
+    .with_system(
+      interact
+        .run_in_state(GameState::Playing)
+        .label("interaction-interact")
+        .after("interaction-focus"),
+    )
+    .with_system(
+      update_interaction_prompt
+        .run_in_state(GameState::Playing)
+        .label("interaction-prompt")
+        .after("interaction-focus"),
+    )
+}
+
+type InteractableWhen = (Without<Player>, Without<Dead>, Without<Offscreen>);
+
+/// Focuses the interactable in range the player is looking at, preferring higher priorities and
+/// then the closest.
+fn update_interaction_focus(
+  mut focus: ResMut<InteractionFocus>,
+  players: Query<(&GlobalTransform, &Facing), With<Player>>,
+  interactables: Query<(Entity, &Interactable, &GlobalTransform), InteractableWhen>,
+) {
+  let focused = players
+    .get_single()
+    .ok()
+    .and_then(|(player_transform, facing)| {
+      let player_position = player_transform.translation().truncate();
+      let facing = facing.as_vec2();
+
+      interactables
+        .iter()
+        .filter_map(|(entity, interactable, transform)| {
+          let offset = transform.translation().truncate() - player_position;
+          let distance = offset.length();
+          let in_front = distance <= FOCUS_TOUCH_DISTANCE
+            || offset.normalize_or_zero().dot(facing) >= FOCUS_MIN_ALIGNMENT;
+
+          (distance <= interactable.radius && in_front).then_some((
+            entity,
+            interactable.priority,
+            distance,
+          ))
+        })
+        .max_by(|(_, priority_a, distance_a), (_, priority_b, distance_b)| {
+          priority_a
+            .cmp(priority_b)
+            .then_with(|| distance_b.total_cmp(distance_a))
+        })
+        .map(|(entity, ..)| entity)
+    });
+
+  if focus.0 != focused {
+    focus.0 = focused;
+  }
+}
+
+fn interact(
+  focus: Res<InteractionFocus>,
+  players: Query<(Entity, &ActionState<TopDownAction>), With<Player>>,
+  mut interact_events: EventWriter<InteractEvent>,
+) {
+  let Some(target) = focus.0 else {
+    return;
+  };
+
+  for (interactor, action_state) in players.iter() {
+    if action_state.just_pressed(TopDownAction::Interact) {
+      interact_events.send(InteractEvent { interactor, target });
+    }
+  }
+}
+
+pub fn spawn_interaction_prompt(mut commands: Commands, ui_assets: Res<UiAssets>) {
+  commands
+    .spawn(NodeBundle {
+      style: Style {
+        size: Size::new(Val::Percent(100.), Val::Auto),
+        position_type: PositionType::Absolute,
+        position: UiRect {
+          bottom: Val::Px(24.),
+          ..default()
+        },
+        justify_content: JustifyContent::Center,
+        ..default()
+      },
+      ..default()
+    })
+    .with_children(|parent| {
+      parent.spawn((
+        ui_assets.text("", 20.).with_style(Style {
+          padding: UiRect::all(Val::Px(4.)),
+          ..default()
+        }),
+        InteractionPrompt,
+      ));
+    });
+}
+
+pub fn despawn_interaction_prompt(
+  mut commands: Commands,
+  mut focus: ResMut<InteractionFocus>,
+  prompts: Query<&Parent, With<InteractionPrompt>>,
+) {
+  focus.0 = None;
+
+  for parent in prompts.iter() {
+    commands.entity(parent.get()).despawn_recursive();
+  }
+}
+
+/// Shows the prompt of the focused interactable, along with the button or key `Interact` is bound
+/// to on the device in use: the gamepad once one is connected, the keyboard otherwise.
+fn update_interaction_prompt(
+  focus: Res<InteractionFocus>,
+  interactables: Query<&Interactable>,
+  input_maps: Query<&InputMap<TopDownAction>, With<Player>>,
+  gamepad: Option<Res<MyGamepad>>,
+  mut prompts: Query<(&mut Text, &mut Visibility), With<InteractionPrompt>>,
+) {
+  let binding = input_maps
+    .get_single()
+    .ok()
+    .and_then(|input_map| binding_label(input_map, TopDownAction::Interact, gamepad.is_some()));
+
+  let prompt = focus
+    .0
+    .and_then(|entity| interactables.get(entity).ok())
+    .map(|interactable| match &binding {
+      Some(binding) => format!("[{binding}] {}", interactable.prompt),
+      None => interactable.prompt.clone(),
+    });
+
+  for (mut text, mut visibility) in prompts.iter_mut() {
+    visibility.is_visible = prompt.is_some();
+    if let Some(prompt) = &prompt {
+      if text.sections[0].value != *prompt {
+        text.sections[0].value = prompt.clone();
+      }
+    }
+  }
+}
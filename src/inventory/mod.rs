@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_asset_loader::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod plugin;
+pub mod screen;
+pub mod systems;
+
+pub const DEFAULT_MAX_STACK: u32 = 99;
+pub const INVENTORY_CAPACITY: usize = 24;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum EquipSlot {
+  Weapon,
+  Armor,
+  Trinket,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+pub enum ItemEffect {
+  #[default]
+  None,
+  /// Consumed on use, restoring the given amount of health.
+  Heal(f32),
+  /// Can be equipped into the given slot.
+  Equip(EquipSlot),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ItemDefinition {
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(default = "default_max_stack")]
+  pub max_stack: u32,
+  #[serde(default)]
+  pub effect: ItemEffect,
+  /// What the item is worth in coins, which shop prices are based on.
+  #[serde(default)]
+  pub value: u32,
+}
+
+fn default_max_stack() -> u32 {
+  DEFAULT_MAX_STACK
+}
+
+/// Every item of the game keyed by its id, loaded from `assets/items/game.items.ron`.
+#[derive(Clone, Debug, Default, Deserialize, TypeUuid)]
+#[serde(transparent)]
+#[uuid = "4f0d9a8e-3b7c-4b5e-9a53-2d8f6c1e7a10"]
+pub struct ItemDefinitions(pub HashMap<String, ItemDefinition>);
+
+impl ItemDefinitions {
+  pub fn get(&self, item: &str) -> Option<&ItemDefinition> {
+    self.0.get(item)
+  }
+
+  pub fn max_stack(&self, item: &str) -> u32 {
+    self
+      .get(item)
+      .map(|definition| definition.max_stack.max(1))
+      .unwrap_or(DEFAULT_MAX_STACK)
+  }
+
+  pub fn value(&self, item: &str) -> u32 {
+    self
+      .get(item)
+      .map(|definition| definition.value)
+      .unwrap_or_default()
+  }
+
+  pub fn name<'a>(&'a self, item: &'a str) -> &'a str {
+    self
+      .get(item)
+      .map(|definition| definition.name.as_str())
+      .unwrap_or(item)
+  }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct ItemAssets {
+  #[asset(path = "items/game.items.ron")]
+  pub definitions: Handle<ItemDefinitions>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ItemStack {
+  pub item: String,
+  pub amount: u32,
+}
+
+#[derive(Component, Clone, Debug, Deserialize, Serialize)]
+pub struct Inventory {
+  pub slots: Vec<ItemStack>,
+  pub capacity: usize,
+  pub equipped: HashMap<EquipSlot, String>,
+}
+
+impl Default for Inventory {
+  fn default() -> Self {
+    Self {
+      slots: Vec::new(),
+      capacity: INVENTORY_CAPACITY,
+      equipped: HashMap::new(),
+    }
+  }
+}
+
+impl Inventory {
+  /// Adds `amount` of `item`, topping up existing stacks before opening new slots. Returns the
+  /// amount that did not fit.
+  pub fn add(&mut self, definitions: &ItemDefinitions, item: &str, mut amount: u32) -> u32 {
+    let max_stack = definitions.max_stack(item);
+
+    for stack in self.slots.iter_mut().filter(|stack| stack.item == item) {
+      let added = amount.min(max_stack.saturating_sub(stack.amount));
+      stack.amount += added;
+      amount -= added;
+    }
+
+    while amount > 0 && self.slots.len() < self.capacity {
+      let added = amount.min(max_stack);
+      self.slots.push(ItemStack {
+        item: item.to_string(),
+        amount: added,
+      });
+      amount -= added;
+    }
+
+    amount
+  }
+
+  /// Removes up to `amount` of `item`, emptying the last stacks first. Returns the amount removed.
+  pub fn remove(&mut self, item: &str, amount: u32) -> u32 {
+    let mut removed = 0;
+
+    for stack in self
+      .slots
+      .iter_mut()
+      .rev()
+      .filter(|stack| stack.item == item)
+    {
+      let taken = (amount - removed).min(stack.amount);
+      stack.amount -= taken;
+      removed += taken;
+    }
+
+    self.slots.retain(|stack| stack.amount > 0);
+    self.unequip_missing();
+
+    removed
+  }
+
+  /// Takes up to `amount` items out of a single slot.
+  pub fn take_from_slot(&mut self, slot: usize, amount: u32) -> Option<ItemStack> {
+    let stack = self.slots.get_mut(slot)?;
+    let taken = amount.min(stack.amount);
+    stack.amount -= taken;
+
+    let item = stack.item.clone();
+    if stack.amount == 0 {
+      self.slots.remove(slot);
+      self.unequip_missing();
+    }
+
+    Some(ItemStack {
+      item,
+      amount: taken,
+    })
+  }
+
+  pub fn count(&self, item: &str) -> u32 {
+    self
+      .slots
+      .iter()
+      .filter(|stack| stack.item == item)
+      .map(|stack| stack.amount)
+      .sum()
+  }
+
+  pub fn is_equipped(&self, item: &str) -> bool {
+    self.equipped.values().any(|equipped| equipped == item)
+  }
+
+  fn unequip_missing(&mut self) {
+    let slots = &self.slots;
+    self
+      .equipped
+      .retain(|_, equipped| slots.iter().any(|stack| stack.item == *equipped));
+  }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InventoryAction {
+  Use,
+  Equip,
+  Drop,
+}
+
+/// Requests `action` to be performed on the stack at `slot` of `owner`'s inventory.
+#[derive(Clone, Copy, Debug)]
+pub struct InventoryEvent {
+  pub owner: Entity,
+  pub slot: usize,
+  pub action: InventoryAction,
+}
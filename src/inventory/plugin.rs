@@ -0,0 +1,29 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::{
+  pause::systems::{freeze_gameplay, resume_gameplay},
+  utils::ron_asset::RonAssetAppExt,
+  GameState,
+};
+
+use super::{
+  screen::{despawn_inventory_screen, spawn_inventory_screen},
+  InventoryEvent, ItemDefinitions,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_ron_asset::<ItemDefinitions>(&["items.ron"])
+      .add_event::<InventoryEvent>()
+      .add_enter_system(GameState::Inventory, spawn_inventory_screen)
+      .add_enter_system(GameState::Inventory, freeze_gameplay)
+      .add_exit_system(GameState::Inventory, despawn_inventory_screen)
+      .add_exit_system(GameState::Inventory, resume_gameplay)
+      .add_system_set(super::systems::add_systems())
+      .add_system_set(super::screen::add_systems());
+  }
+}
@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  player::Player,
+  ui::{self, UiAssets},
+  GameState,
+};
+
+use super::{Inventory, InventoryAction, InventoryEvent, ItemAssets, ItemDefinitions, ItemEffect};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("inventory-screen")
+    .with_system(
+      refresh_inventory_screen
+        .run_in_state(GameState::Inventory)
+        .label("inventory-screen-refresh"),
+    )
+    .with_system(
+      press_inventory_buttons
+        .run_in_state(GameState::Inventory)
+        .label("inventory-screen-buttons")
+        .before("inventory-apply-events"),
+    )
+}
+
+#[derive(Component)]
+pub struct InventoryScreen;
+
+#[derive(Component)]
+pub struct InventoryList;
+
+#[derive(Component, Clone, Copy)]
+pub struct InventoryButton {
+  pub slot: usize,
+  pub action: InventoryAction,
+}
+
+pub fn spawn_inventory_screen(mut commands: Commands, ui_assets: Res<UiAssets>) {
+  commands
+    .spawn((ui::overlay(Color::rgba(0., 0., 0., 0.5)), InventoryScreen))
+    .with_children(|overlay| {
+      overlay.spawn(ui::panel()).with_children(|panel| {
+        panel.spawn(ui_assets.text("Inventory", 32.));
+        panel.spawn((
+          NodeBundle {
+            style: Style {
+              flex_direction: FlexDirection::Column,
+              margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(8.), Val::Px(0.)),
+              ..default()
+            },
+            ..default()
+          },
+          InventoryList,
+        ));
+      });
+    });
+}
+
+pub fn despawn_inventory_screen(
+  mut commands: Commands,
+  screens: Query<Entity, With<InventoryScreen>>,
+) {
+  for screen in screens.iter() {
+    commands.entity(screen).despawn_recursive();
+  }
+}
+
+/// Rebuilds the rows of the list whenever it's first shown or the player's inventory changes.
+fn refresh_inventory_screen(
+  mut commands: Commands,
+  lists: Query<Entity, Added<InventoryList>>,
+  all_lists: Query<Entity, With<InventoryList>>,
+  inventories: Query<(&Inventory, ChangeTrackers<Inventory>), With<Player>>,
+  ui_assets: Res<UiAssets>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+) {
+  let Ok((inventory, inventory_tracker)) = inventories.get_single() else {
+    return;
+  };
+
+  let Some(definitions) = item_definitions.get(&item_assets.definitions) else {
+    return;
+  };
+
+  let stale_lists: Vec<Entity> = if inventory_tracker.is_changed() {
+    all_lists.iter().collect()
+  } else {
+    lists.iter().collect()
+  };
+
+  for list in stale_lists {
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|list| {
+      if inventory.slots.is_empty() {
+        list.spawn(ui_assets.text("Empty", 20.));
+      }
+
+      for (slot, stack) in inventory.slots.iter().enumerate() {
+        let effect = definitions
+          .get(&stack.item)
+          .map(|definition| definition.effect)
+          .unwrap_or_default();
+
+        let mut label = format!("{} x{}", definitions.name(&stack.item), stack.amount);
+        if inventory.is_equipped(&stack.item) {
+          label.push_str(" (equipped)");
+        }
+
+        list
+          .spawn(NodeBundle {
+            style: Style {
+              flex_direction: FlexDirection::Row,
+              align_items: AlignItems::Center,
+              ..default()
+            },
+            ..default()
+          })
+          .with_children(|row| {
+            row.spawn(ui_assets.text(label, 20.).with_style(Style {
+              flex_grow: 1.,
+              ..default()
+            }));
+
+            let actions = match effect {
+              ItemEffect::Heal(_) => vec![("Use", InventoryAction::Use)],
+              ItemEffect::Equip(_) => vec![("Equip", InventoryAction::Equip)],
+              ItemEffect::None => vec![],
+            };
+
+            for (text, action) in actions
+              .into_iter()
+              .chain(std::iter::once(("Drop", InventoryAction::Drop)))
+            {
+              row
+                .spawn((ui::button(), InventoryButton { slot, action }))
+                .with_children(|button| {
+                  button.spawn(ui_assets.text(text, 16.));
+                });
+            }
+          });
+      }
+    });
+  }
+}
+
+fn press_inventory_buttons(
+  buttons: Query<(&Interaction, &InventoryButton), Changed<Interaction>>,
+  players: Query<Entity, With<Player>>,
+  mut inventory_events: EventWriter<InventoryEvent>,
+) {
+  let Ok(owner) = players.get_single() else {
+    return;
+  };
+
+  for (interaction, &InventoryButton { slot, action }) in buttons.iter() {
+    if *interaction == Interaction::Clicked {
+      inventory_events.send(InventoryEvent {
+        owner,
+        slot,
+        action,
+      });
+    }
+  }
+}
@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+  combat::Health,
+  loot::{systems::spawn_pickup, PickupCollected},
+  player::{state_machine::TopDownAction, Player},
+  shop::{give_item, Wallet},
+  GameState,
+};
+
+use super::{Inventory, InventoryAction, InventoryEvent, ItemAssets, ItemDefinitions, ItemEffect};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("inventory")
+    .with_system(
+      collect_into_inventory
+        .run_in_state(GameState::Playing)
+        .label("inventory-collect")
+        .after("loot-collect-pickups"),
+    )
+    .with_system(
+      open_inventory
+        .run_in_state(GameState::Playing)
+        .label("inventory-open"),
+    )
+    .with_system(
+      close_inventory
+        .run_in_state(GameState::Inventory)
+        .label("inventory-close"),
+    )
+    .with_system(apply_inventory_events.label("inventory-apply-events"))
+}
+
+fn collect_into_inventory(
+  mut collected_events: EventReader<PickupCollected>,
+  mut inventories: Query<&mut Inventory>,
+  mut wallets: Query<&mut Wallet>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+) {
+  let Some(definitions) = item_definitions.get(&item_assets.definitions) else {
+    return;
+  };
+
+  for PickupCollected {
+    collector,
+    item,
+    amount,
+    ..
+  } in collected_events.iter()
+  {
+    if let Ok(mut inventory) = inventories.get_mut(*collector) {
+      let mut wallet = wallets.get_mut(*collector).ok();
+      let leftover = give_item(
+        &mut inventory,
+        wallet.as_deref_mut(),
+        definitions,
+        item,
+        *amount,
+      );
+
+      if leftover > 0 {
+        warn!("Inventory full, {leftover} {item} were lost");
+      }
+    }
+  }
+}
+
+fn open_inventory(
+  mut commands: Commands,
+  players: Query<&ActionState<TopDownAction>, With<Player>>,
+) {
+  if players
+    .iter()
+    .any(|action_state| action_state.just_pressed(TopDownAction::Menus))
+  {
+    commands.insert_resource(NextState(GameState::Inventory));
+  }
+}
+
+fn close_inventory(
+  mut commands: Commands,
+  players: Query<&ActionState<TopDownAction>, With<Player>>,
+) {
+  if players.iter().any(|action_state| {
+    action_state.just_pressed(TopDownAction::Menus)
+      || action_state.just_pressed(TopDownAction::Pause)
+  }) {
+    commands.insert_resource(NextState(GameState::Playing));
+  }
+}
+
+fn apply_inventory_events(
+  mut commands: Commands,
+  mut inventory_events: EventReader<InventoryEvent>,
+  mut owners: Query<(&mut Inventory, Option<&mut Health>, &GlobalTransform)>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+) {
+  let Some(definitions) = item_definitions.get(&item_assets.definitions) else {
+    return;
+  };
+
+  for &InventoryEvent {
+    owner,
+    slot,
+    action,
+  } in inventory_events.iter()
+  {
+    let Ok((mut inventory, health, transform)) = owners.get_mut(owner) else {
+      continue;
+    };
+
+    let Some(stack) = inventory.slots.get(slot).cloned() else {
+      continue;
+    };
+
+    let effect = definitions
+      .get(&stack.item)
+      .map(|definition| definition.effect)
+      .unwrap_or_default();
+
+    match (action, effect) {
+      (InventoryAction::Use, ItemEffect::Heal(amount)) => {
+        let Some(mut health) = health else {
+          continue;
+        };
+
+        if health.current < health.max {
+          health.current = (health.current + amount).min(health.max);
+          inventory.take_from_slot(slot, 1);
+        }
+      }
+      (InventoryAction::Equip, ItemEffect::Equip(equip_slot)) => {
+        if inventory.equipped.get(&equip_slot) == Some(&stack.item) {
+          inventory.equipped.remove(&equip_slot);
+        } else {
+          inventory.equipped.insert(equip_slot, stack.item);
+        }
+      }
+      (InventoryAction::Drop, _) => {
+        if let Some(dropped) = inventory.take_from_slot(slot, stack.amount) {
+          // Far enough below the owner that it isn't collected right back
+          spawn_pickup(
+            &mut commands,
+            dropped.item,
+            dropped.amount,
+            transform.translation() - Vec3::Y * 20.,
+          );
+        }
+      }
+      (action, _) => {
+        warn!(
+          "{action:?} is not supported by {}",
+          definitions.name(&stack.item)
+        );
+      }
+    }
+  }
+}
@@ -1,10 +1,43 @@
+pub mod animation;
+pub mod bark;
+pub mod blackboard;
+pub mod camera;
+pub mod clock;
+pub mod combat;
+pub mod companion;
+pub mod dialogue;
 pub mod enemy;
+pub mod faction;
+pub mod interaction;
+pub mod inventory;
+pub mod loading;
+pub mod loot;
 pub mod map;
+pub mod menu;
+pub mod navigation;
+pub mod npc;
+pub mod obstacle;
+pub mod pause;
 pub mod player;
+pub mod quest;
+pub mod save;
+pub mod shop;
+pub mod targeting;
+pub mod transition;
+pub mod ui;
 pub mod utils;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 pub enum GameState {
   AssetLoading,
+  MainMenu,
+  LevelLoading,
   Playing,
+  Inventory,
+  Dialogue,
+  QuestLog,
+  Shop,
+  Paused,
+  GameOver,
+  Victory,
 }
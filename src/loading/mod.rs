@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+
+pub mod plugin;
+pub mod systems;
+
+/// Loaded straight through the asset server, as the loading screen is shown before the
+/// [`UiAssets`](crate::ui::UiAssets) collection is ready.
+pub const LOADING_FONT_PATH: &str = "fonts/DejaVuSans.ttf";
+
+#[derive(Component)]
+pub struct LoadingScreen;
+
+/// The filled part of the progress bar.
+#[derive(Component)]
+pub struct LoadingBar;
+
+#[derive(Component)]
+pub struct LoadingText;
@@ -0,0 +1,19 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::systems::{despawn_loading_screen, spawn_loading_screen};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_enter_system(GameState::AssetLoading, spawn_loading_screen)
+      .add_exit_system(GameState::AssetLoading, despawn_loading_screen)
+      .add_enter_system(GameState::LevelLoading, spawn_loading_screen)
+      .add_exit_system(GameState::LevelLoading, despawn_loading_screen)
+      .add_system_set(super::systems::add_systems());
+  }
+}
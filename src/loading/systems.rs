@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use iyes_progress::prelude::*;
+
+use crate::{ui, GameState};
+
+use super::{LoadingBar, LoadingScreen, LoadingText, LOADING_FONT_PATH};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new().label("loading").with_system(
+    update_loading_screen
+      .run_if_resource_exists::<ProgressCounter>()
+      .label("loading-update-screen"),
+  )
+}
+
+pub fn spawn_loading_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+  let text_style = TextStyle {
+    font: asset_server.load(LOADING_FONT_PATH),
+    font_size: 24.,
+    color: ui::TEXT_COLOR,
+  };
+
+  commands
+    .spawn((ui::overlay(Color::rgb(0.05, 0.05, 0.08)), LoadingScreen))
+    .with_children(|overlay| {
+      overlay.spawn((
+        TextBundle::from_section("Loading...", text_style),
+        LoadingText,
+      ));
+
+      // Progress bar track
+      overlay
+        .spawn(NodeBundle {
+          style: Style {
+            size: Size::new(Val::Px(320.), Val::Px(16.)),
+            margin: UiRect::all(Val::Px(12.)),
+            ..default()
+          },
+          background_color: ui::BUTTON_COLOR.into(),
+          ..default()
+        })
+        .with_children(|track| {
+          track.spawn((
+            NodeBundle {
+              style: Style {
+                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                ..default()
+              },
+              background_color: ui::BUTTON_PRESSED_COLOR.into(),
+              ..default()
+            },
+            LoadingBar,
+          ));
+        });
+    });
+}
+
+pub fn despawn_loading_screen(mut commands: Commands, screens: Query<Entity, With<LoadingScreen>>) {
+  for screen in screens.iter() {
+    commands.entity(screen).despawn_recursive();
+  }
+}
+
+fn update_loading_screen(
+  progress_counter: Res<ProgressCounter>,
+  state: Res<CurrentState<GameState>>,
+  mut bars: Query<&mut Style, With<LoadingBar>>,
+  mut texts: Query<&mut Text, With<LoadingText>>,
+) {
+  let Progress { done, total } = progress_counter.progress();
+  let ratio = if total == 0 {
+    0.
+  } else {
+    done as f32 / total as f32
+  };
+
+  for mut style in bars.iter_mut() {
+    style.size.width = Val::Percent(ratio * 100.);
+  }
+
+  let label = match state.0 {
+    GameState::AssetLoading => "Loading assets",
+    _ => "Preparing level",
+  };
+
+  for mut text in texts.iter_mut() {
+    text.sections[0].value = format!("{label}... {done}/{total}");
+  }
+}
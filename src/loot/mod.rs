@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+  interaction::Interactable,
+  map::{ColliderBundle, SensorBundle},
+  utils::ldtk::*,
+};
+
+pub mod plugin;
+pub mod systems;
+
+/// Frame of the `mystic_woods/objects/chest_*.png` sheets where the lid is fully open.
+pub const CHEST_OPEN_FRAME: usize = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LootEntry {
+  pub item: String,
+  pub amount: u32,
+  /// Probability in `[0, 1]` of this entry dropping when the table is rolled.
+  pub chance: f32,
+}
+
+impl LootEntry {
+  pub fn new(item: impl Into<String>, amount: u32, chance: f32) -> Self {
+    Self {
+      item: item.into(),
+      amount,
+      chance,
+    }
+  }
+}
+
+impl std::str::FromStr for LootEntry {
+  type Err = String;
+
+  /// Parses the `item`, `item*amount` or `item*amount@chance` notation used by LDtk fields.
+  fn from_str(entry: &str) -> Result<Self, Self::Err> {
+    let (entry, chance) = match entry.split_once('@') {
+      Some((entry, chance)) => (
+        entry,
+        chance.trim().parse::<f32>().map_err(|e| e.to_string())?,
+      ),
+      None => (entry, 1.),
+    };
+
+    let (item, amount) = match entry.split_once('*') {
+      Some((item, amount)) => (
+        item,
+        amount.trim().parse::<u32>().map_err(|e| e.to_string())?,
+      ),
+      None => (entry, 1),
+    };
+
+    Ok(Self::new(item.trim(), amount, chance))
+  }
+}
+
+/// What an entity leaves behind when it is opened or defeated.
+#[derive(Clone, Debug, Default, Component)]
+pub struct LootTable(pub Vec<LootEntry>);
+
+impl LootTable {
+  /// Rolls every entry independently, returning the `(item, amount)` pairs that dropped.
+  pub fn roll(&self) -> Vec<(String, u32)> {
+    self
+      .0
+      .iter()
+      .filter(|entry| fastrand::f32() < entry.chance)
+      .map(|entry| (entry.item.clone(), entry.amount))
+      .collect()
+  }
+}
+
+impl From<EntityInstance> for LootTable {
+  fn from(entity_instance: EntityInstance) -> LootTable {
+    let authored = field_strings(&entity_instance, "Loot")
+      .iter()
+      .filter_map(|entry| match entry.parse::<LootEntry>() {
+        Ok(entry) => Some(entry),
+        Err(error) => {
+          warn!("Invalid loot entry {entry:?}: {error}");
+          None
+        }
+      })
+      .collect::<Vec<_>>();
+
+    if !authored.is_empty() {
+      return LootTable(authored);
+    }
+
+    match entity_instance.identifier.as_ref() {
+      "Enemy" | "Bandit" => LootTable(vec![
+        LootEntry::new("coin", 2, 0.75),
+        LootEntry::new("potion", 1, 0.15),
+      ]),
+      "Chest" => LootTable(vec![LootEntry::new("coin", 10, 1.)]),
+      _ => LootTable::default(),
+    }
+  }
+}
+
+/// An item lying in the world, collected when the player's collider overlaps it.
+#[derive(Clone, Debug, Component)]
+pub struct Pickup {
+  pub item: String,
+  pub amount: u32,
+}
+
+impl Default for Pickup {
+  fn default() -> Self {
+    Self {
+      item: "coin".into(),
+      amount: 1,
+    }
+  }
+}
+
+impl From<EntityInstance> for Pickup {
+  fn from(entity_instance: EntityInstance) -> Pickup {
+    let default = Pickup::default();
+
+    Pickup {
+      item: field_string(&entity_instance, "Item").unwrap_or(default.item),
+      amount: field_int(&entity_instance, "Amount")
+        .map(|amount| amount.max(1) as u32)
+        .unwrap_or(default.amount),
+    }
+  }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct PickupBundle {
+  #[from_entity_instance]
+  pub pickup: Pickup,
+
+  #[bundle]
+  pub sensor: SensorBundle,
+
+  #[sprite_sheet_bundle]
+  #[bundle]
+  sprite_bundle: SpriteSheetBundle,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
+
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Chest {
+  pub opened: bool,
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct ChestBundle {
+  pub chest: Chest,
+  #[from_entity_instance]
+  pub interactable: Interactable,
+
+  #[from_entity_instance]
+  pub loot_table: LootTable,
+
+  #[from_entity_instance]
+  #[bundle]
+  pub collider_bundle: ColliderBundle,
+
+  #[sprite_sheet_bundle]
+  #[bundle]
+  sprite_bundle: SpriteSheetBundle,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
+
+/// Sent when `collector` picks up `amount` of `item`. The `pickup` entity is despawned at the end
+/// of the frame.
+#[derive(Clone, Debug)]
+pub struct PickupCollected {
+  pub pickup: Entity,
+  pub collector: Entity,
+  pub item: String,
+  pub amount: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ChestOpened {
+  pub chest: Entity,
+  pub opener: Entity,
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::{App, Plugin};
+
+use super::{ChestOpened, PickupCollected};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_event::<PickupCollected>()
+      .add_event::<ChestOpened>()
+      .add_system_set(super::systems::add_systems());
+  }
+}
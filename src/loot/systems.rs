@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::IntoConditionalSystem;
+
+use crate::{
+  combat::DeathEvent,
+  interaction::{InteractEvent, Interactable},
+  map::{systems::started_with_player, DespawnWithWorld, SensorBundle},
+  player::Player,
+  GameState,
+};
+
+use super::{Chest, ChestOpened, LootTable, Pickup, PickupCollected, CHEST_OPEN_FRAME};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("loot")
+    .with_system(
+      collect_pickups
+        .run_in_state(GameState::Playing)
+        .label("loot-collect-pickups"),
+    )
+    .with_system(
+      open_chests
+        .run_in_state(GameState::Playing)
+        .label("loot-open-chests")
+        .after("interaction-interact"),
+    )
+    .with_system(
+      drop_loot_on_death
+        .run_in_state(GameState::Playing)
+        .label("loot-drop-on-death")
+        .after("combat-apply-damage"),
+    )
+}
+
+/// Spawns a single pickup entity at `translation`.
+pub fn spawn_pickup(commands: &mut Commands, item: String, amount: u32, translation: Vec3) {
+  commands.spawn((
+    SpriteBundle {
+      sprite: Sprite {
+        color: item_color(&item),
+        custom_size: Some(Vec2::splat(6.)),
+        ..default()
+      },
+      transform: Transform::from_translation(translation),
+      ..default()
+    },
+    SensorBundle::new(Collider::ball(4.)),
+    Pickup { item, amount },
+    DespawnWithWorld,
+  ));
+}
+
+/// Spawns the rolled loot around `translation`, scattering the drops so they don't stack.
+pub fn spawn_loot(commands: &mut Commands, loot: Vec<(String, u32)>, translation: Vec3) {
+  let count = loot.len();
+
+  for (index, (item, amount)) in loot.into_iter().enumerate() {
+    let offset = if count > 1 {
+      let angle = index as f32 / count as f32 * std::f32::consts::TAU;
+      Vec2::from_angle(angle).extend(0.) * 10.
+    } else {
+      Vec3::ZERO
+    };
+
+    spawn_pickup(commands, item, amount, translation + offset);
+  }
+}
+
+/// Placeholder tint for dropped items until they get their own icons.
+fn item_color(item: &str) -> Color {
+  match item {
+    "coin" => Color::GOLD,
+    "potion" => Color::CRIMSON,
+    "key" => Color::SILVER,
+    _ => Color::WHITE,
+  }
+}
+
+fn collect_pickups(
+  mut commands: Commands,
+  mut collision_events: EventReader<CollisionEvent>,
+  mut collected_events: EventWriter<PickupCollected>,
+  pickups: Query<&Pickup>,
+  players: Query<(), With<Player>>,
+) {
+  // The same pickup can be touched twice in a frame before its despawn is applied
+  let mut collected = HashSet::new();
+
+  for (other, player) in collision_events
+    .iter()
+    .filter_map(|event| started_with_player(event, &players))
+  {
+    let Ok(pickup) = pickups.get(other) else {
+      continue;
+    };
+
+    if !collected.insert(other) {
+      continue;
+    }
+
+    collected_events.send(PickupCollected {
+      pickup: other,
+      collector: player,
+      item: pickup.item.clone(),
+      amount: pickup.amount,
+    });
+    commands.entity(other).despawn_recursive();
+  }
+}
+
+/// Opens the chests the player interacts with, dropping their loot. An open chest can't be
+/// interacted with anymore.
+fn open_chests(
+  mut commands: Commands,
+  mut interact_events: EventReader<InteractEvent>,
+  mut opened_events: EventWriter<ChestOpened>,
+  mut chests: Query<(
+    &mut Chest,
+    &LootTable,
+    &GlobalTransform,
+    Option<&mut TextureAtlasSprite>,
+  )>,
+) {
+  for &InteractEvent {
+    interactor,
+    target: chest_entity,
+  } in interact_events.iter()
+  {
+    let Ok((mut chest, loot_table, transform, sprite)) = chests.get_mut(chest_entity) else {
+      continue;
+    };
+
+    if chest.opened {
+      continue;
+    }
+
+    chest.opened = true;
+    commands.entity(chest_entity).remove::<Interactable>();
+    if let Some(mut sprite) = sprite {
+      sprite.index = CHEST_OPEN_FRAME;
+    }
+
+    // Drop the loot in front of the chest so it doesn't spawn inside its collider
+    spawn_loot(
+      &mut commands,
+      loot_table.roll(),
+      transform.translation() - Vec3::Y * 16.,
+    );
+    opened_events.send(ChestOpened {
+      chest: chest_entity,
+      opener: interactor,
+    });
+  }
+}
+
+fn drop_loot_on_death(
+  mut commands: Commands,
+  mut death_events: EventReader<DeathEvent>,
+  loot_tables: Query<&LootTable, Without<Chest>>,
+) {
+  for &DeathEvent {
+    entity,
+    translation,
+    ..
+  } in death_events.iter()
+  {
+    if let Ok(loot_table) = loot_tables.get(entity) {
+      spawn_loot(&mut commands, loot_table.roll(), translation);
+    }
+  }
+}
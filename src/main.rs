@@ -7,35 +7,47 @@ use iyes_loopless::prelude::*;
 use leafwing_input_manager::prelude::InputManagerPlugin;
 use seldom_state::prelude::*;
 
-use iyes_progress::{ProgressCounter, ProgressPlugin};
+use iyes_progress::ProgressPlugin;
 use npcs_ai_game::{
-  enemy::{self, state_machine::Near},
-  map,
+  animation, bark, blackboard, camera, clock, combat,
+  companion::{self, state_machine::HasLeaderOrTarget},
+  dialogue,
+  enemy::{
+    self,
+    state_machine::{HasLead, Near, Pursuing},
+  },
+  faction, interaction, inventory, loading, loot,
+  map::{self, terrain::TerrainAppExt},
+  menu, navigation, npc, obstacle, pause,
   player::{self, state_machine::TopDownAction},
-  GameState,
+  quest, save, shop, targeting, transition, ui, GameState,
 };
 
-#[derive(AssetCollection, Resource)]
-struct ImageAssets {
-  #[asset(path = "map/npcs.ldtk")]
-  map: Handle<LdtkAsset>,
-}
-
 fn main() {
   let mut app = App::new();
   app.add_loopless_state(GameState::AssetLoading);
 
   LoadingState::new(GameState::AssetLoading)
-    .continue_to_state(GameState::Playing)
-    .with_collection::<ImageAssets>()
+    .continue_to_state(GameState::MainMenu)
+    .with_collection::<map::MapAssets>()
+    .with_collection::<ui::UiAssets>()
+    .with_collection::<inventory::ItemAssets>()
+    .with_collection::<dialogue::DialogueAssets>()
+    .with_collection::<quest::QuestAssets>()
+    .with_collection::<shop::ShopAssets>()
+    .with_collection::<bark::BarkAssets>()
     .build(&mut app);
 
   app
     .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
     .add_plugin(ProgressPlugin::new(GameState::AssetLoading))
+    .add_plugin(ProgressPlugin::new(GameState::LevelLoading).continue_to(GameState::Playing))
     .add_plugin(LdtkPlugin)
     .add_plugin(StateMachinePlugin)
     .add_plugin(TriggerPlugin::<Near>::default())
+    .add_plugin(TriggerPlugin::<Pursuing>::default())
+    .add_plugin(TriggerPlugin::<HasLead>::default())
+    .add_plugin(TriggerPlugin::<HasLeaderOrTarget>::default())
     .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
     // .add_plugin(RapierDebugRenderPlugin::default())
     .add_plugin(InputManagerPlugin::<TopDownAction>::default())
@@ -46,32 +58,58 @@ fn main() {
       ..Default::default()
     })
     .insert_resource(LevelSelection::Index(0))
-    // ============ Enter system ============
-    .add_enter_system(GameState::Playing, init)
+    // ============ Startup system ============
+    .add_startup_system(init)
     // ============ (My scope) ============
     .add_plugin(player::plugin::All)
     .add_plugin(enemy::plugin::All)
     .add_plugin(map::plugin::All)
+    .add_plugin(camera::plugin::All)
+    .add_plugin(combat::plugin::All)
+    .add_plugin(loot::plugin::All)
+    .add_plugin(inventory::plugin::All)
+    .add_plugin(pause::plugin::All)
+    .add_plugin(menu::plugin::All)
+    .add_plugin(loading::plugin::All)
+    .add_plugin(navigation::plugin::All)
+    .add_plugin(obstacle::plugin::All)
+    .add_plugin(save::plugin::All)
+    .add_plugin(transition::plugin::All)
+    .add_plugin(ui::plugin::All)
+    .add_plugin(animation::plugin::All)
+    .add_plugin(dialogue::plugin::All)
+    .add_plugin(interaction::plugin::All)
+    .add_plugin(clock::plugin::All)
+    .add_plugin(npc::plugin::All)
+    .add_plugin(quest::plugin::All)
+    .add_plugin(shop::plugin::All)
+    .add_plugin(faction::plugin::All)
+    .add_plugin(targeting::plugin::All)
+    .add_plugin(blackboard::plugin::All)
+    .add_plugin(bark::plugin::All)
+    .add_plugin(companion::plugin::All)
     // ============ Ldtk entity registry ============
     .register_ldtk_entity::<player::PlayerBundle>("Player")
     .register_ldtk_entity::<enemy::EnemyBundle>("Enemy")
-    .register_ldtk_int_cell::<map::WallBundle>(1)
-    // ============ Stage system ============
-    .add_system_to_stage(CoreStage::PostUpdate, print_progress);
+    .register_ldtk_entity::<enemy::EnemyBundle>("Guard")
+    .register_ldtk_entity::<enemy::EnemyBundle>("Bandit")
+    .register_ldtk_entity::<loot::PickupBundle>("Pickup")
+    .register_ldtk_entity::<loot::ChestBundle>("Chest")
+    .register_ldtk_entity::<transition::DoorBundle>("Door")
+    .register_ldtk_entity::<transition::EntryPointBundle>("EntryPoint")
+    .register_ldtk_entity::<obstacle::ObstacleBundle>("Gate")
+    .register_ldtk_entity::<obstacle::ObstacleBundle>("Rock")
+    .register_ldtk_entity::<obstacle::ObstacleBundle>("Crate")
+    .register_ldtk_entity::<obstacle::LeverBundle>("Lever")
+    .register_ldtk_entity::<npc::NpcBundle>("Npc")
+    .register_ldtk_entity::<companion::CompanionBundle>("Companion")
+    .register_ldtk_entity::<npc::WaypointBundle>("Waypoint")
+    .register_ldtk_entity::<quest::QuestAreaBundle>("QuestArea")
+    .register_terrain_types();
 
   app.run();
 }
 
-fn init(mut commands: Commands, images: Res<ImageAssets>) {
-  commands.spawn(Camera2dBundle::default());
-  commands.spawn(LdtkWorldBundle {
-    ldtk_handle: images.map.clone(),
-    ..Default::default()
-  });
-}
-
-fn print_progress(progress: Option<Res<ProgressCounter>>) {
-  if let Some(progress) = progress {
-    info!("Current progress: {:?}", progress.progress());
-  }
+fn init(mut commands: Commands) {
+  commands.spawn((Camera2dBundle::default(), camera::GameCamera::default()));
 }
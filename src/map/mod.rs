@@ -1,13 +1,26 @@
 use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use self::terrain::Terrain;
 
 pub mod plugin;
 pub mod systems;
+pub mod terrain;
+
+#[derive(AssetCollection, Resource)]
+pub struct MapAssets {
+  #[asset(path = "map/npcs.ldtk")]
+  pub map: Handle<LdtkAsset>,
+}
 
-const ASPECT_RATIO: f32 = 16. / 9.;
+/// Marks top level entities that don't belong to the LDtk hierarchy (e.g. dropped loot) but should
+/// be despawned together with the world when a run ends.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct DespawnWithWorld;
 
 #[derive(Clone, Default, Bundle, LdtkIntCell)]
 pub struct ColliderBundle {
@@ -30,12 +43,30 @@ impl From<EntityInstance> for ColliderBundle {
         rotation_constraints: LockedAxes::ROTATION_LOCKED,
         ..Default::default()
       },
-      "Enemy" => ColliderBundle {
+      "Enemy" | "Guard" | "Bandit" => ColliderBundle {
         collider: Collider::cuboid(2., 2.),
         rigid_body: RigidBody::KinematicPositionBased,
         rotation_constraints: LockedAxes::ROTATION_LOCKED,
         ..Default::default()
       },
+      "Npc" | "Companion" => ColliderBundle {
+        collider: Collider::cuboid(6., 6.),
+        rigid_body: RigidBody::KinematicPositionBased,
+        rotation_constraints: LockedAxes::ROTATION_LOCKED,
+        ..Default::default()
+      },
+      "Chest" => ColliderBundle {
+        collider: Collider::cuboid(7., 5.),
+        rigid_body: RigidBody::Fixed,
+        rotation_constraints: LockedAxes::ROTATION_LOCKED,
+        ..Default::default()
+      },
+      "Gate" | "Rock" | "Crate" => ColliderBundle {
+        collider: Collider::cuboid(8., 8.),
+        rigid_body: RigidBody::Fixed,
+        rotation_constraints: LockedAxes::ROTATION_LOCKED,
+        ..Default::default()
+      },
       _ => ColliderBundle::default(),
     }
   }
@@ -57,19 +88,152 @@ impl From<IntGridCell> for ColliderBundle {
   }
 }
 
+/// A sensor collider that reports collisions with the (kinematic) player.
+#[derive(Clone, Bundle)]
+pub struct SensorBundle {
+  pub collider: Collider,
+  pub sensor: Sensor,
+  pub active_events: ActiveEvents,
+  pub active_collision_types: ActiveCollisionTypes,
+}
+
+impl SensorBundle {
+  pub fn new(collider: Collider) -> Self {
+    Self {
+      collider,
+      sensor: Sensor,
+      active_events: ActiveEvents::COLLISION_EVENTS,
+      // Sensors without a rigid body are static, and static-kinematic pairs are ignored by default
+      active_collision_types: ActiveCollisionTypes::default()
+        | ActiveCollisionTypes::KINEMATIC_STATIC
+        | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+    }
+  }
+}
+
+impl Default for SensorBundle {
+  fn default() -> Self {
+    Self::new(Collider::ball(4.))
+  }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum WallDirection {
+  Up,
+  Down,
+  Left,
+  Right,
+}
+
+impl WallDirection {
+  pub const ALL: [WallDirection; 4] = [
+    WallDirection::Up,
+    WallDirection::Down,
+    WallDirection::Left,
+    WallDirection::Right,
+  ];
+
+  pub fn as_vec2(&self) -> Vec2 {
+    match self {
+      WallDirection::Up => Vec2::Y,
+      WallDirection::Down => Vec2::NEG_Y,
+      WallDirection::Left => Vec2::NEG_X,
+      WallDirection::Right => Vec2::X,
+    }
+  }
+
+  /// The directions a movement of `translation` heads towards, at most one per axis.
+  pub fn from_translation(translation: Vec2) -> impl Iterator<Item = WallDirection> {
+    let horizontal = match translation.x {
+      x if x > 0. => Some(WallDirection::Right),
+      x if x < 0. => Some(WallDirection::Left),
+      _ => None,
+    };
+    let vertical = match translation.y {
+      y if y > 0. => Some(WallDirection::Up),
+      y if y < 0. => Some(WallDirection::Down),
+      _ => None,
+    };
+    horizontal.into_iter().chain(vertical)
+  }
+}
+
+/// Gives an entity a [`WallSensor`] on each side, and remembers on which sides it's touching a
+/// wall.
 #[derive(Clone, Default, Component, Resource)]
-pub struct WallDetection;
+pub struct WallDetection {
+  touching: HashSet<WallDirection>,
+}
+
+impl WallDetection {
+  pub fn is_touching_wall(&self, direction: WallDirection) -> bool {
+    self.touching.contains(&direction)
+  }
 
+  pub fn is_touching_any_wall(&self) -> bool {
+    !self.touching.is_empty()
+  }
+}
+
+/// A thin sensor along one side of a [`WallDetection`] entity, tracking the wall colliders (and
+/// blocking obstacles) it overlaps.
 #[derive(Component)]
 pub struct WallSensor {
   pub wall_detection_entity: Entity,
+  pub direction: WallDirection,
   pub intersecting_wall_entities: HashSet<Entity>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Wall;
 
-#[derive(Clone, Debug, Default, Bundle, LdtkIntCell)]
+/// Marks the merged rectangle colliders spawned for the [`Wall`] tiles of a level.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct WallCollider;
+
+/// A rectangle of wall tiles merged into a single collider, in the [`GridCoords`] of its level.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct WallRect {
+  pub left: i32,
+  pub right: i32,
+  pub top: i32,
+  pub bottom: i32,
+}
+
+impl WallRect {
+  pub fn tiles(self) -> impl Iterator<Item = GridCoords> {
+    let WallRect {
+      left,
+      right,
+      top,
+      bottom,
+    } = self;
+    (bottom..=top).flat_map(move |y| (left..=right).map(move |x| GridCoords { x, y }))
+  }
+}
+
+/// The wall tiles of a level and the merged colliders covering them.
+#[derive(Clone, Debug, Default)]
+pub struct LevelWalls {
+  pub tiles: HashSet<GridCoords>,
+  /// The wall tiles whose [`Terrain`] blocks sight.
+  pub opaque: HashSet<GridCoords>,
+  pub colliders: HashMap<Entity, WallRect>,
+  pub covered_by: HashMap<GridCoords, Entity>,
+}
+
+/// Remembers how the walls of every spawned level were merged into colliders, so adding or
+/// removing a wall only merges the colliders around it again.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct WallMerger {
+  pub levels: HashMap<Entity, LevelWalls>,
+  /// The level and tile of every wall entity, to find them again once they are removed.
+  pub walls: HashMap<Entity, (Entity, GridCoords)>,
+}
+
+/// A tile whose [`Terrain`] blocks movement, see [`terrain::TERRAIN_TYPES`].
+#[derive(Clone, Debug, Bundle)]
 pub struct WallBundle {
   wall: Wall,
+  terrain: Terrain,
 }
@@ -1 +1,23 @@
-crate::add_all_systems!(All);
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::{
+  systems::{despawn_world, spawn_world},
+  WallMerger,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<WallMerger>()
+      .add_enter_system(GameState::LevelLoading, spawn_world)
+      .add_enter_system(GameState::MainMenu, despawn_world)
+      .add_exit_system(GameState::GameOver, despawn_world)
+      .add_exit_system(GameState::Victory, despawn_world)
+      .add_system_set(super::systems::add_systems());
+  }
+}
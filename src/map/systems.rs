@@ -3,78 +3,54 @@ use std::collections::{HashMap, HashSet};
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::{prelude::*, rapier::prelude::Cuboid};
+use iyes_loopless::prelude::IntoConditionalSystem;
+use iyes_progress::prelude::*;
 
-use crate::player::Player;
+use crate::{
+  navigation::{NavigationChanged, WalkabilityGrid},
+  obstacle::Obstacle,
+  player::Player,
+  utils::position::Pos,
+  GameState,
+};
 
-use super::{Wall, WallDetection, WallSensor, ASPECT_RATIO};
+use super::{
+  terrain::Terrain, DespawnWithWorld, MapAssets, SensorBundle, Wall, WallCollider, WallDetection,
+  WallDirection, WallMerger, WallRect, WallSensor,
+};
 
 pub fn add_systems() -> SystemSet {
   SystemSet::new()
     .label("map")
-    .with_system(camera_fit_inside_current_level.label("camera-fit-inside-current-level"))
     .with_system(spawn_wall_collision.label("spawn-wall-collision"))
     .with_system(spawn_wall_sensor.label("spawn-wall-sensor"))
-    .with_system(update_level_selection.label("update-level-selection"))
+    .with_system(update_wall_sensors.label("update-wall-sensors"))
+    .with_system(
+      wall_collision_progress
+        .track_progress()
+        .run_in_state(GameState::LevelLoading)
+        .label("wall-collision-progress")
+        .after("spawn-wall-collision"),
+    )
 }
 
-// Factor this type out into a type alias
-type LevelQuery<'w, 's> = Query<
-  'w,
-  's,
-  (&'static Transform, &'static Handle<LdtkLevel>),
-  (Without<OrthographicProjection>, Without<Player>),
->;
-
-pub fn camera_fit_inside_current_level(
-  mut camera_query: Query<(&mut OrthographicProjection, &mut Transform), Without<Player>>,
-  player_query: Query<&Transform, With<Player>>,
-  level_query: LevelQuery,
-  level_selection: Res<LevelSelection>,
-  ldtk_levels: Res<Assets<LdtkLevel>>,
-) {
-  if let Ok(Transform {
-    translation: player_translation,
-    ..
-  }) = player_query.get_single()
-  {
-    let player_translation = *player_translation;
-
-    let (mut orthographic_projection, mut camera_transform) = camera_query.single_mut();
-
-    for (level_transform, level_handle) in level_query.iter() {
-      if let Some(ldtk_level) = ldtk_levels.get(level_handle) {
-        let level = &ldtk_level.level;
-        if level_selection.is_match(&0, level) {
-          let level_ratio = level.px_wid as f32 / ldtk_level.level.px_hei as f32;
-
-          orthographic_projection.scaling_mode = bevy::render::camera::ScalingMode::None;
-          orthographic_projection.bottom = 0.;
-          orthographic_projection.left = 0.;
-          if level_ratio > ASPECT_RATIO {
-            // level is wider than the screen
-            orthographic_projection.top = (level.px_hei as f32 / 9.).round() * 9.;
-            orthographic_projection.right = orthographic_projection.top * ASPECT_RATIO;
-            camera_transform.translation.x = (player_translation.x
-              - level_transform.translation.x
-              - orthographic_projection.right / 2.)
-              .clamp(0., level.px_wid as f32 - orthographic_projection.right);
-            camera_transform.translation.y = 0.;
-          } else {
-            // level is taller than the screen
-            orthographic_projection.right = (level.px_wid as f32 / 16.).round() * 16.;
-            orthographic_projection.top = orthographic_projection.right / ASPECT_RATIO;
-            camera_transform.translation.y = (player_translation.y
-              - level_transform.translation.y
-              - orthographic_projection.top / 2.)
-              .clamp(0., level.px_hei as f32 - orthographic_projection.top);
-            camera_transform.translation.x = 0.;
-          }
+/// Spawns the LDtk world when a run starts loading.
+pub fn spawn_world(mut commands: Commands, map_assets: Res<MapAssets>) {
+  commands.spawn(LdtkWorldBundle {
+    ldtk_handle: map_assets.map.clone(),
+    ..Default::default()
+  });
+}
 
-          camera_transform.translation.x += level_transform.translation.x;
-          camera_transform.translation.y += level_transform.translation.y;
-        }
-      }
-    }
+/// Despawns the LDtk world, and with it every level, entity and collider, so the next
+/// [`spawn_world`] starts a fresh run.
+pub fn despawn_world(
+  mut commands: Commands,
+  worlds: Query<Entity, With<Handle<LdtkAsset>>>,
+  loose_entities: Query<Entity, With<DespawnWithWorld>>,
+) {
+  for entity in worlds.iter().chain(loose_entities.iter()) {
+    commands.entity(entity).despawn_recursive();
   }
 }
 
@@ -90,204 +66,310 @@ pub fn camera_fit_inside_current_level(
 /// spawning the collisions later, we can minimize
 /// the amount of colliding entities.
 ///
-/// The algorithm used here is a nice compromise
-/// between simplicity, speed, and a small number
-/// of rectangle colliders. In basic terms, it
-/// will: 1. consider where the walls are
-/// 2. combine wall tiles into flat "plates" in
-/// each individual row 3. combine the plates into
-/// rectangles across multiple rows wherever
-/// possible 4. spawn colliders for each rectangle
+/// The walls are merged with [`merge_wall_rects`].
+/// The [`WallMerger`] remembers which collider
+/// covers each tile, so when walls are added or
+/// removed later on only the colliders covering
+/// or touching the changed tiles are despawned
+/// and their tiles merged again.
 pub fn spawn_wall_collision(
   mut commands: Commands,
-  wall_query: Query<(&GridCoords, &Parent), Added<Wall>>,
+  mut merger: ResMut<WallMerger>,
+  wall_query: Query<(Entity, &GridCoords, &Terrain, &Parent), Added<Wall>>,
+  removed_walls: RemovedComponents<Wall>,
   parent_query: Query<&Parent, Without<Wall>>,
-  level_query: Query<(Entity, &Handle<LdtkLevel>)>,
+  level_query: Query<&Handle<LdtkLevel>>,
   levels: Res<Assets<LdtkLevel>>,
+  mut grid: ResMut<WalkabilityGrid>,
+  mut navigation_events: EventWriter<NavigationChanged>,
 ) {
-  /// Represents a wide wall that is 1 tile tall
-  /// Used to spawn wall collisions
-  #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
-  struct Plate {
-    left: i32,
-    right: i32,
-  }
-
-  /// A simple rectangle type representing a
-  /// wall of any size
-  #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
-  struct Rect {
-    left: i32,
-    right: i32,
-    top: i32,
-    bottom: i32,
-  }
-
-  // Consider where the walls are
-  // storing them as GridCoords in a HashSet for
-  // quick, easy lookup
-  //
-  // The key of this map will be the entity of the
-  // level the wall belongs to. This has two
-  // consequences in the resulting collision
-  // entities: 1. it forces the walls to be
-  // split along level boundaries 2. it lets us
+  // Consider where the walls changed, keyed by
+  // the entity of the level the wall belongs to.
+  // This has two consequences in the resulting
+  // collision entities: 1. it forces the walls to
+  // be split along level boundaries 2. it lets us
   // easily add the collision entities as children
   // of the appropriate level entity
-  let mut level_to_wall_locations: HashMap<Entity, HashSet<GridCoords>> = HashMap::new();
+  let mut changed_tiles: HashMap<Entity, Vec<GridCoords>> = HashMap::new();
 
-  wall_query.for_each(|(&grid_coords, parent)| {
+  wall_query.for_each(|(wall_entity, &grid_coords, terrain, parent)| {
     // An intgrid tile's direct parent will be a layer
     // entity, not the level entity To get the
     // level entity, you need the tile's grandparent.
     // This is where parent_query comes in.
     if let Ok(grandparent) = parent_query.get(parent.get()) {
-      level_to_wall_locations
-        .entry(grandparent.get())
-        .or_insert(HashSet::new())
-        .insert(grid_coords);
+      let level_entity = grandparent.get();
+      merger
+        .walls
+        .insert(wall_entity, (level_entity, grid_coords));
+      let level_walls = merger.levels.entry(level_entity).or_default();
+      if terrain.0.blocks_sight() {
+        level_walls.opaque.insert(grid_coords);
+      }
+      if level_walls.tiles.insert(grid_coords) {
+        changed_tiles
+          .entry(level_entity)
+          .or_default()
+          .push(grid_coords);
+      }
     }
   });
 
-  if !wall_query.is_empty() {
-    level_query.for_each(|(level_entity, level_handle)| {
-      if let Some(level_walls) = level_to_wall_locations.get(&level_entity) {
-        let level = levels
-          .get(level_handle)
-          .expect("Level should be loaded by this point");
-
-        let LayerInstance {
-          c_wid: width,
-          c_hei: height,
-          grid_size,
-          ..
-        } = level
-          .level
-          .layer_instances
-          .clone()
-          .expect("Level asset should have layers")[0];
-
-        // combine wall tiles into flat "plates" in each individual row
-        let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
-
-        for y in 0..height {
-          let mut row_plates: Vec<Plate> = Vec::new();
-          let mut plate_start = None;
-
-          // + 1 to the width so the algorithm "terminates" plates that touch the right
-          // edge
-          for x in 0..width + 1 {
-            match (plate_start, level_walls.contains(&GridCoords { x, y })) {
-              (Some(s), false) => {
-                row_plates.push(Plate {
-                  left: s,
-                  right: x - 1,
-                });
-                plate_start = None;
-              }
-              (None, true) => plate_start = Some(x),
-              _ => (),
-            }
-          }
+  for wall_entity in removed_walls.iter() {
+    let Some((level_entity, grid_coords)) = merger.walls.remove(&wall_entity) else {
+      continue;
+    };
+
+    if let Some(level_walls) = merger.levels.get_mut(&level_entity) {
+      level_walls.opaque.remove(&grid_coords);
+      if level_walls.tiles.remove(&grid_coords) {
+        changed_tiles
+          .entry(level_entity)
+          .or_default()
+          .push(grid_coords);
+      }
+    }
+  }
+
+  // Colliders of despawned levels went away with them
+  merger
+    .levels
+    .retain(|level_entity, _| level_query.contains(*level_entity));
+
+  for (level_entity, tiles) in changed_tiles {
+    let (Ok(level_handle), Some(level_walls)) = (
+      level_query.get(level_entity),
+      merger.levels.get_mut(&level_entity),
+    ) else {
+      continue;
+    };
 
-          plate_stack.push(row_plates);
+    let ldtk_level = levels
+      .get(level_handle)
+      .expect("Level should be loaded by this point");
+
+    let LayerInstance {
+      c_hei: height,
+      grid_size,
+      ..
+    } = ldtk_level
+      .level
+      .layer_instances
+      .clone()
+      .expect("Level asset should have layers")[0];
+
+    // Replace every collider covering or touching a changed tile, so the tiles can merge with
+    // their neighbours again
+    let mut replaced_colliders = HashSet::new();
+    for &GridCoords { x, y } in tiles.iter() {
+      for neighbour in [
+        GridCoords { x, y },
+        GridCoords { x: x - 1, y },
+        GridCoords { x: x + 1, y },
+        GridCoords { x, y: y - 1 },
+        GridCoords { x, y: y + 1 },
+      ] {
+        if let Some(&collider) = level_walls.covered_by.get(&neighbour) {
+          replaced_colliders.insert(collider);
         }
+      }
+    }
+
+    let mut region: HashSet<GridCoords> = tiles
+      .iter()
+      .filter(|grid_coords| level_walls.tiles.contains(grid_coords))
+      .copied()
+      .collect();
 
-        // combine "plates" into rectangles across multiple rows
-        let mut wall_rects: Vec<Rect> = Vec::new();
-        let mut previous_rects: HashMap<Plate, Rect> = HashMap::new();
-
-        // an extra empty row so the algorithm "terminates" the rects that touch the top
-        // edge
-        plate_stack.push(Vec::new());
-
-        for (y, row) in plate_stack.iter().enumerate() {
-          let mut current_rects: HashMap<Plate, Rect> = HashMap::new();
-          for plate in row {
-            if let Some(previous_rect) = previous_rects.remove(plate) {
-              current_rects.insert(
-                *plate,
-                Rect {
-                  top: previous_rect.top + 1,
-                  ..previous_rect
-                },
-              );
-            } else {
-              current_rects.insert(
-                *plate,
-                Rect {
-                  bottom: y as i32,
-                  top: y as i32,
-                  left: plate.left,
-                  right: plate.right,
-                },
-              );
-            }
+    for collider in replaced_colliders {
+      if let Some(wall_rect) = level_walls.colliders.remove(&collider) {
+        for grid_coords in wall_rect.tiles() {
+          level_walls.covered_by.remove(&grid_coords);
+          if level_walls.tiles.contains(&grid_coords) {
+            region.insert(grid_coords);
           }
+        }
+      }
+      commands.entity(collider).despawn_recursive();
+    }
 
-          // Any plates that weren't removed above have terminated
-          wall_rects.append(&mut previous_rects.values().copied().collect());
-          previous_rects = current_rects;
+    commands.entity(level_entity).with_children(|level| {
+      // Spawn colliders for every rectangle..
+      // Making the collider a child of the level serves two purposes:
+      // 1. Adjusts the transforms to be relative to the level for free
+      // 2. the colliders will be despawned automatically when levels unload
+      for wall_rect in merge_wall_rects(&region) {
+        let collider = level
+          .spawn((
+            Collider::cuboid(
+              (wall_rect.right as f32 - wall_rect.left as f32 + 1.) * grid_size as f32 / 2.,
+              (wall_rect.top as f32 - wall_rect.bottom as f32 + 1.) * grid_size as f32 / 2.,
+            ),
+            RigidBody::Fixed,
+            Friction {
+              coefficient: 0.1,
+              combine_rule: CoefficientCombineRule::Min,
+            },
+            Transform::from_xyz(
+              (wall_rect.left + wall_rect.right + 1) as f32 * grid_size as f32 / 2.,
+              (wall_rect.bottom + wall_rect.top + 1) as f32 * grid_size as f32 / 2.,
+              0.,
+            ),
+            GlobalTransform::default(),
+            WallCollider,
+          ))
+          .id();
+
+        level_walls.colliders.insert(collider, wall_rect);
+        for grid_coords in wall_rect.tiles() {
+          level_walls.covered_by.insert(grid_coords, collider);
         }
+      }
+    });
+
+    // Keep path finding in step when walls change after the level has been baked
+    if grid.is_baked_for(&ldtk_level.level) {
+      let mut changed = Vec::new();
+      for grid_coords in tiles {
+        let pos = Pos(grid_coords.x, height - grid_coords.y - 1);
 
-        commands.entity(level_entity).with_children(|level| {
-          // Spawn colliders for every rectangle..
-          // Making the collider a child of the level serves two purposes:
-          // 1. Adjusts the transforms to be relative to the level for free
-          // 2. the colliders will be despawned automatically when levels unload
-          for wall_rect in wall_rects {
-            level.spawn((
-              Collider::cuboid(
-                (wall_rect.right as f32 - wall_rect.left as f32 + 1.) * grid_size as f32 / 2.,
-                (wall_rect.top as f32 - wall_rect.bottom as f32 + 1.) * grid_size as f32 / 2.,
-              ),
-              RigidBody::Fixed,
-              Friction {
-                coefficient: 0.1,
-                combine_rule: CoefficientCombineRule::Min,
-              },
-              Transform::from_xyz(
-                (wall_rect.left + wall_rect.right + 1) as f32 * grid_size as f32 / 2.,
-                (wall_rect.bottom + wall_rect.top + 1) as f32 * grid_size as f32 / 2.,
-                0.,
-              ),
-              GlobalTransform::default(),
-            ));
+        if level_walls.opaque.contains(&grid_coords) {
+          grid.opaque.insert(pos.clone());
+        } else {
+          grid.opaque.remove(&pos);
+        }
+
+        let is_wall = level_walls.tiles.contains(&grid_coords);
+        if is_wall != grid.walls.contains(&pos) {
+          if is_wall {
+            grid.walls.insert(pos.clone());
+          } else {
+            grid.walls.remove(&pos);
           }
-        });
+          changed.push(pos);
+        }
       }
-    });
+
+      if !changed.is_empty() {
+        navigation_events.send(NavigationChanged { tiles: changed });
+      }
+    }
   }
 }
 
-pub fn update_level_selection(
-  level_query: Query<(&Handle<LdtkLevel>, &Transform), Without<Player>>,
-  player_query: Query<&Transform, With<Player>>,
-  mut level_selection: ResMut<LevelSelection>,
-  ldtk_levels: Res<Assets<LdtkLevel>>,
-) {
-  for (level_handle, level_transform) in level_query.iter() {
-    if let Some(ldtk_level) = ldtk_levels.get(level_handle) {
-      let level_bounds = bevy::math::Rect {
-        min: Vec2::new(level_transform.translation.x, level_transform.translation.y),
-        max: Vec2::new(
-          level_transform.translation.x + ldtk_level.level.px_wid as f32,
-          level_transform.translation.y + ldtk_level.level.px_hei as f32,
-        ),
-      };
+/// Merges wall tiles into as few rectangles as
+/// possible. In basic terms, it will:
+/// 1. combine wall tiles into flat "plates" in
+/// each individual row 2. combine the plates into
+/// rectangles across multiple rows wherever
+/// possible
+fn merge_wall_rects(walls: &HashSet<GridCoords>) -> Vec<WallRect> {
+  /// Represents a wide wall that is 1 tile tall
+  /// Used to spawn wall collisions
+  #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+  struct Plate {
+    left: i32,
+    right: i32,
+  }
+
+  let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) = (
+    walls.iter().map(|grid_coords| grid_coords.x).min(),
+    walls.iter().map(|grid_coords| grid_coords.x).max(),
+    walls.iter().map(|grid_coords| grid_coords.y).min(),
+    walls.iter().map(|grid_coords| grid_coords.y).max(),
+  ) else {
+    return Vec::new();
+  };
+
+  // combine wall tiles into flat "plates" in each individual row
+  let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
 
-      for player_transform in player_query.iter() {
-        if player_transform.translation.x < level_bounds.max.x
-          && player_transform.translation.x > level_bounds.min.x
-          && player_transform.translation.y < level_bounds.max.y
-          && player_transform.translation.y > level_bounds.min.y
-        // && !level_selection
-        //     .is_match(&0, &ldtk_level.level)
-        {
-          *level_selection = LevelSelection::Iid(ldtk_level.level.iid.clone());
+  for y in min_y..=max_y {
+    let mut row_plates: Vec<Plate> = Vec::new();
+    let mut plate_start = None;
+
+    // + 1 to the width so the algorithm "terminates" plates that touch the right
+    // edge
+    for x in min_x..=max_x + 1 {
+      match (plate_start, walls.contains(&GridCoords { x, y })) {
+        (Some(s), false) => {
+          row_plates.push(Plate {
+            left: s,
+            right: x - 1,
+          });
+          plate_start = None;
         }
+        (None, true) => plate_start = Some(x),
+        _ => (),
       }
     }
+
+    plate_stack.push(row_plates);
+  }
+
+  // combine "plates" into rectangles across multiple rows
+  let mut wall_rects: Vec<WallRect> = Vec::new();
+  let mut previous_rects: HashMap<Plate, WallRect> = HashMap::new();
+
+  // an extra empty row so the algorithm "terminates" the rects that touch the top
+  // edge
+  plate_stack.push(Vec::new());
+
+  for (row, plates) in plate_stack.iter().enumerate() {
+    let y = min_y + row as i32;
+    let mut current_rects: HashMap<Plate, WallRect> = HashMap::new();
+    for plate in plates {
+      if let Some(previous_rect) = previous_rects.remove(plate) {
+        current_rects.insert(
+          *plate,
+          WallRect {
+            top: previous_rect.top + 1,
+            ..previous_rect
+          },
+        );
+      } else {
+        current_rects.insert(
+          *plate,
+          WallRect {
+            bottom: y,
+            top: y,
+            left: plate.left,
+            right: plate.right,
+          },
+        );
+      }
+    }
+
+    // Any plates that weren't removed above have terminated
+    wall_rects.append(&mut previous_rects.values().copied().collect());
+    previous_rects = current_rects;
+  }
+
+  wall_rects
+}
+
+/// Reports the level as ready once its contents are spawned and its walls have been merged into
+/// colliders.
+fn wall_collision_progress(
+  levels: Query<&Children, With<Handle<LdtkLevel>>>,
+  walls: Query<(), With<Wall>>,
+  wall_colliders: Query<(), With<WallCollider>>,
+) -> Progress {
+  let level_spawned = levels.iter().any(|children| !children.is_empty());
+
+  (level_spawned && (walls.is_empty() || !wall_colliders.is_empty())).into()
+}
+
+/// Returns the `(other, player)` pair of a collision start event if one of the two colliders
+/// belongs to a player.
+pub fn started_with_player(
+  event: &CollisionEvent,
+  players: &Query<(), With<Player>>,
+) -> Option<(Entity, Entity)> {
+  match *event {
+    CollisionEvent::Started(a, b, _) if players.contains(a) => Some((b, a)),
+    CollisionEvent::Started(a, b, _) if players.contains(b) => Some((a, b)),
+    _ => None,
   }
 }
 
@@ -298,18 +380,86 @@ pub fn spawn_wall_sensor(
   for (entity, shape, transform) in detect_wall_for.iter() {
     if let Some(Cuboid { half_extents }) = shape.raw.0.as_cuboid() {
       commands.entity(entity).with_children(|builder| {
-        builder.spawn((
-          Sensor,
-          Collider::cuboid(half_extents.x / 2., 2.),
-          ActiveEvents::COLLISION_EVENTS,
-          Transform::from_translation(Vec3::new(0., -half_extents.y, 0.) / transform.scale),
-          GlobalTransform::default(),
-          WallSensor {
-            wall_detection_entity: entity,
-            intersecting_wall_entities: HashSet::new(),
-          },
-        ));
+        for direction in WallDirection::ALL {
+          // Thin along the side it's on and half as long as it, so corners don't count
+          let collider = match direction {
+            WallDirection::Up | WallDirection::Down => Collider::cuboid(half_extents.x / 2., 2.),
+            WallDirection::Left | WallDirection::Right => Collider::cuboid(2., half_extents.y / 2.),
+          };
+          let offset = direction.as_vec2() * Vec2::new(half_extents.x, half_extents.y);
+
+          builder.spawn((
+            SensorBundle::new(collider),
+            Transform::from_translation(offset.extend(0.) / transform.scale),
+            GlobalTransform::default(),
+            WallSensor {
+              wall_detection_entity: entity,
+              direction,
+              intersecting_wall_entities: HashSet::new(),
+            },
+          ));
+        }
       });
     }
   }
 }
+
+/// Fills every [`WallSensor`] from Rapier's collision events, and updates the sides its
+/// [`WallDetection`] entity is touching a wall on. Obstacles only count as walls while blocking.
+pub fn update_wall_sensors(
+  mut collision_events: EventReader<CollisionEvent>,
+  mut sensors: Query<&mut WallSensor>,
+  walls: Query<Option<&Obstacle>, Or<(With<WallCollider>, With<Obstacle>)>>,
+  mut detections: Query<&mut WallDetection>,
+) {
+  let is_wall = |entity: Entity| {
+    walls.get(entity).map_or(false, |obstacle| {
+      obstacle.map_or(true, |obstacle| obstacle.blocking)
+    })
+  };
+
+  for collision_event in collision_events.iter() {
+    let (a, b, started) = match *collision_event {
+      CollisionEvent::Started(a, b, _) => (a, b, true),
+      CollisionEvent::Stopped(a, b, _) => (a, b, false),
+    };
+
+    for (sensor, wall) in [(a, b), (b, a)] {
+      let Ok(mut sensor) = sensors.get_mut(sensor) else {
+        continue;
+      };
+
+      if !started {
+        sensor.intersecting_wall_entities.remove(&wall);
+      } else if is_wall(wall) {
+        sensor.intersecting_wall_entities.insert(wall);
+      }
+    }
+  }
+
+  for mut sensor in sensors.iter_mut() {
+    // Merged wall colliders are replaced when walls change, and gates stop blocking when opened
+    if sensor
+      .intersecting_wall_entities
+      .iter()
+      .any(|wall| !is_wall(*wall))
+    {
+      sensor
+        .intersecting_wall_entities
+        .retain(|wall| is_wall(*wall));
+    }
+
+    let Ok(mut detection) = detections.get_mut(sensor.wall_detection_entity) else {
+      continue;
+    };
+
+    let touching = !sensor.intersecting_wall_entities.is_empty();
+    if detection.is_touching_wall(sensor.direction) != touching {
+      if touching {
+        detection.touching.insert(sensor.direction);
+      } else {
+        detection.touching.remove(&sensor.direction);
+      }
+    }
+  }
+}
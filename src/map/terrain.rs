@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::LayerInstance, prelude::*};
+
+use super::{Wall, WallBundle};
+
+/// What an IntGrid value means for movement, collisions and path finding.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TerrainKind {
+  /// Blocks movement and sight.
+  Solid,
+  /// Blocks movement but can be seen (and later shot) across.
+  Water,
+  /// Walkable at `speed` times the normal speed, e.g. grass or mud.
+  Slow { speed: f32 },
+  /// Walkable, but hurts whoever stands on it, e.g. spikes.
+  Damaging { damage_per_second: f32 },
+  /// A drop that can be walked down (southwards) but not climbed back up.
+  Ledge,
+}
+
+impl TerrainKind {
+  pub fn blocks_movement(&self) -> bool {
+    matches!(self, TerrainKind::Solid | TerrainKind::Water)
+  }
+
+  pub fn blocks_sight(&self) -> bool {
+    matches!(self, TerrainKind::Solid)
+  }
+
+  pub fn speed_multiplier(&self) -> f32 {
+    match self {
+      TerrainKind::Slow { speed } => *speed,
+      _ => 1.,
+    }
+  }
+
+  /// The cost of stepping onto a tile of this terrain when path finding. Slow tiles cost as many
+  /// steps as they take to cross, and damaging ones are avoided unless there's no way around.
+  pub fn path_cost(&self) -> u32 {
+    match self {
+      TerrainKind::Slow { speed } => (1. / speed.max(0.1)).ceil() as u32,
+      TerrainKind::Damaging { .. } => 10,
+      _ => 1,
+    }
+  }
+}
+
+/// Gives `value` of the IntGrid layer `layer` the semantics of `kind`.
+#[derive(Clone, Copy, Debug)]
+pub struct TerrainType {
+  pub layer: &'static str,
+  pub value: i32,
+  pub kind: TerrainKind,
+}
+
+/// Every IntGrid value the game understands. Values missing here have no gameplay effect.
+pub const TERRAIN_TYPES: &[TerrainType] = &[
+  TerrainType {
+    layer: "Walls",
+    value: 1,
+    kind: TerrainKind::Solid,
+  },
+  TerrainType {
+    layer: "Walls",
+    value: 2,
+    kind: TerrainKind::Water,
+  },
+  TerrainType {
+    layer: "Walls",
+    value: 3,
+    kind: TerrainKind::Damaging {
+      damage_per_second: 4.,
+    },
+  },
+  TerrainType {
+    layer: "Walls",
+    value: 4,
+    kind: TerrainKind::Ledge,
+  },
+  TerrainType {
+    layer: "Grass",
+    value: 1,
+    kind: TerrainKind::Slow { speed: 0.6 },
+  },
+  TerrainType {
+    layer: "Grass",
+    value: 2,
+    kind: TerrainKind::Slow { speed: 0.4 },
+  },
+];
+
+pub fn terrain_kind(layer: &str, value: i32) -> Option<TerrainKind> {
+  TERRAIN_TYPES
+    .iter()
+    .find(|terrain_type| terrain_type.layer == layer && terrain_type.value == value)
+    .map(|terrain_type| terrain_type.kind)
+}
+
+/// The semantics of an IntGrid tile, looked up in [`TERRAIN_TYPES`] when the tile spawns.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Terrain(pub TerrainKind);
+
+impl Terrain {
+  fn from_int_grid_cell(int_grid_cell: IntGridCell, layer_instance: &LayerInstance) -> Self {
+    Terrain(
+      terrain_kind(&layer_instance.identifier, int_grid_cell.value)
+        .expect("Only registered terrain types are spawned"),
+    )
+  }
+}
+
+/// A walkable tile with special semantics. Tiles blocking movement spawn as a [`WallBundle`]
+/// instead, so their colliders get merged.
+#[derive(Clone, Debug, Bundle)]
+pub struct TerrainBundle {
+  pub terrain: Terrain,
+}
+
+impl LdtkIntCell for TerrainBundle {
+  fn bundle_int_cell(int_grid_cell: IntGridCell, layer_instance: &LayerInstance) -> Self {
+    TerrainBundle {
+      terrain: Terrain::from_int_grid_cell(int_grid_cell, layer_instance),
+    }
+  }
+}
+
+impl LdtkIntCell for WallBundle {
+  fn bundle_int_cell(int_grid_cell: IntGridCell, layer_instance: &LayerInstance) -> Self {
+    WallBundle {
+      wall: Wall,
+      terrain: Terrain::from_int_grid_cell(int_grid_cell, layer_instance),
+    }
+  }
+}
+
+pub trait TerrainAppExt {
+  /// Registers a bundle for every entry of [`TERRAIN_TYPES`] on its own layer.
+  fn register_terrain_types(&mut self) -> &mut Self;
+}
+
+impl TerrainAppExt for App {
+  fn register_terrain_types(&mut self) -> &mut Self {
+    for terrain_type in TERRAIN_TYPES {
+      if terrain_type.kind.blocks_movement() {
+        self.register_ldtk_int_cell_for_layer::<WallBundle>(terrain_type.layer, terrain_type.value);
+      } else {
+        self.register_ldtk_int_cell_for_layer::<TerrainBundle>(
+          terrain_type.layer,
+          terrain_type.value,
+        );
+      }
+    }
+
+    self
+  }
+}
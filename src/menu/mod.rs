@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+pub mod plugin;
+pub mod systems;
+
+/// Root of the title, game over and victory screens.
+#[derive(Component)]
+pub struct MenuScreen;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuButton {
+  NewGame,
+  Load(usize),
+  Retry,
+  MainMenu,
+  Quit,
+}
@@ -0,0 +1,23 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::systems::{
+  despawn_menu_screen, spawn_game_over_screen, spawn_main_menu_screen, spawn_victory_screen,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_enter_system(GameState::MainMenu, spawn_main_menu_screen)
+      .add_exit_system(GameState::MainMenu, despawn_menu_screen)
+      .add_enter_system(GameState::GameOver, spawn_game_over_screen)
+      .add_exit_system(GameState::GameOver, despawn_menu_screen)
+      .add_enter_system(GameState::Victory, spawn_victory_screen)
+      .add_exit_system(GameState::Victory, despawn_menu_screen)
+      .add_system_set(super::systems::add_systems());
+  }
+}
@@ -0,0 +1,143 @@
+use bevy::{app::AppExit, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  combat::{Dead, DeathEvent},
+  enemy::Enemy,
+  player::Player,
+  save::{slot_exists, LoadGame, SAVE_SLOTS},
+  ui::{self, UiAssets},
+  GameState,
+};
+
+use super::{MenuButton, MenuScreen};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("menu")
+    .with_system(press_menu_buttons.label("menu-buttons"))
+    .with_system(
+      end_run
+        .run_in_state(GameState::Playing)
+        .label("menu-end-run")
+        .after("combat-apply-damage"),
+    )
+}
+
+fn spawn_menu_screen(
+  commands: &mut Commands,
+  ui_assets: &UiAssets,
+  title: &str,
+  buttons: &[(MenuButton, String)],
+) {
+  commands
+    .spawn((ui::overlay(Color::rgb(0.05, 0.05, 0.08)), MenuScreen))
+    .with_children(|overlay| {
+      overlay.spawn(ui::panel()).with_children(|panel| {
+        panel.spawn(ui_assets.text(title, 48.));
+
+        for (button, text) in buttons {
+          panel
+            .spawn((ui::button(), *button))
+            .with_children(|parent| {
+              parent.spawn(ui_assets.text(text.clone(), 24.));
+            });
+        }
+      });
+    });
+}
+
+pub fn spawn_main_menu_screen(mut commands: Commands, ui_assets: Res<UiAssets>) {
+  let mut buttons = vec![(MenuButton::NewGame, "New game".to_string())];
+  buttons.extend(
+    (1..=SAVE_SLOTS)
+      .filter(|slot| slot_exists(*slot))
+      .map(|slot| (MenuButton::Load(slot), format!("Load slot {slot}"))),
+  );
+  buttons.push((MenuButton::Quit, "Quit".to_string()));
+
+  spawn_menu_screen(&mut commands, &ui_assets, "NPCs", &buttons);
+}
+
+pub fn spawn_game_over_screen(mut commands: Commands, ui_assets: Res<UiAssets>) {
+  spawn_menu_screen(
+    &mut commands,
+    &ui_assets,
+    "Game over",
+    &[
+      (MenuButton::Retry, "Try again".to_string()),
+      (MenuButton::MainMenu, "Main menu".to_string()),
+    ],
+  );
+}
+
+pub fn spawn_victory_screen(mut commands: Commands, ui_assets: Res<UiAssets>) {
+  spawn_menu_screen(
+    &mut commands,
+    &ui_assets,
+    "Victory!",
+    &[
+      (MenuButton::NewGame, "Play again".to_string()),
+      (MenuButton::MainMenu, "Main menu".to_string()),
+    ],
+  );
+}
+
+pub fn despawn_menu_screen(mut commands: Commands, screens: Query<Entity, With<MenuScreen>>) {
+  for screen in screens.iter() {
+    commands.entity(screen).despawn_recursive();
+  }
+}
+
+fn press_menu_buttons(
+  mut commands: Commands,
+  buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+  mut load_events: EventWriter<LoadGame>,
+  mut exit: EventWriter<AppExit>,
+) {
+  for (interaction, button) in buttons.iter() {
+    if *interaction != Interaction::Clicked {
+      continue;
+    }
+
+    match button {
+      MenuButton::NewGame => {
+        commands.insert_resource(LevelSelection::Index(0));
+        commands.insert_resource(NextState(GameState::LevelLoading));
+      }
+      MenuButton::Load(slot) => load_events.send(LoadGame { slot: *slot }),
+      // The world is torn down when leaving the game over screen, so this restarts the level
+      MenuButton::Retry => commands.insert_resource(NextState(GameState::LevelLoading)),
+      MenuButton::MainMenu => commands.insert_resource(NextState(GameState::MainMenu)),
+      MenuButton::Quit => exit.send(AppExit),
+    }
+  }
+}
+
+/// Ends the run with a game over when the player dies, or with a victory once the last enemy
+/// falls.
+fn end_run(
+  mut commands: Commands,
+  mut death_events: EventReader<DeathEvent>,
+  players: Query<(), With<Player>>,
+  enemies: Query<Entity, (With<Enemy>, Without<Dead>)>,
+) {
+  // `Dead` is only inserted once commands are applied, so this frame's deaths are tracked by hand
+  let mut dead_enemies = Vec::new();
+
+  for DeathEvent { entity, .. } in death_events.iter() {
+    if players.contains(*entity) {
+      commands.insert_resource(NextState(GameState::GameOver));
+      return;
+    }
+
+    if enemies.contains(*entity) {
+      dead_enemies.push(*entity);
+    }
+  }
+
+  if !dead_enemies.is_empty() && enemies.iter().all(|enemy| dead_enemies.contains(&enemy)) {
+    commands.insert_resource(NextState(GameState::Victory));
+  }
+}
@@ -0,0 +1,351 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use astar_pathfinding::astar;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
+
+use crate::{
+  map::terrain::{terrain_kind, TerrainKind},
+  utils::position::Pos,
+};
+
+pub mod plugin;
+pub mod systems;
+
+pub const TILE_SIZE: i32 = 16;
+
+/// Which tiles of the current level can be walked on and what their terrain is, baked once per
+/// level from its IntGrid layers so path finding doesn't have to walk the LDtk layers every frame.
+///
+/// Positions follow the LDtk convention: `(0, 0)` is the top left tile and `y` grows downwards.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct WalkabilityGrid {
+  pub level_iid: Option<String>,
+  pub width: i32,
+  pub height: i32,
+  pub walls: HashSet<Pos>,
+  /// Tiles that can't be seen across, a subset of the `walls` (water can be).
+  pub opaque: HashSet<Pos>,
+  /// Walkable tiles with a [`TerrainKind`], plain floor is left out.
+  pub terrain: HashMap<Pos, TerrainKind>,
+  /// Tiles blocked at runtime, e.g. by closed gates, on top of the static `walls`.
+  pub blocked: HashSet<Pos>,
+}
+
+impl WalkabilityGrid {
+  pub fn from_level(level: &Level) -> Self {
+    let mut grid = WalkabilityGrid {
+      level_iid: Some(level.iid.clone()),
+      width: level.px_wid / TILE_SIZE,
+      height: level.px_hei / TILE_SIZE,
+      walls: HashSet::new(),
+      opaque: HashSet::new(),
+      terrain: HashMap::new(),
+      blocked: HashSet::new(),
+    };
+
+    for layer_instance in level.layer_instances.iter().flatten() {
+      for (index, &value) in layer_instance.int_grid_csv.iter().enumerate() {
+        let Some(kind) = terrain_kind(&layer_instance.identifier, value) else {
+          continue;
+        };
+
+        let index = index as i32;
+        let pos = Pos(index % layer_instance.c_wid, index / layer_instance.c_wid);
+        if kind.blocks_sight() {
+          grid.opaque.insert(pos.clone());
+        }
+        if kind.blocks_movement() {
+          grid.walls.insert(pos);
+        } else {
+          grid.terrain.insert(pos, kind);
+        }
+      }
+    }
+
+    grid
+  }
+
+  pub fn is_baked_for(&self, level: &Level) -> bool {
+    self.level_iid.as_deref() == Some(level.iid.as_str())
+  }
+
+  pub fn in_bounds(&self, pos: &Pos) -> bool {
+    pos.0 >= 0 && pos.1 >= 0 && pos.0 < self.width && pos.1 < self.height
+  }
+
+  pub fn is_walkable(&self, pos: &Pos) -> bool {
+    self.in_bounds(pos) && !self.walls.contains(pos) && !self.blocked.contains(pos)
+  }
+
+  pub fn terrain_at(&self, pos: &Pos) -> Option<TerrainKind> {
+    self.terrain.get(pos).copied()
+  }
+
+  pub fn is_ledge(&self, pos: &Pos) -> bool {
+    self.terrain_at(pos) == Some(TerrainKind::Ledge)
+  }
+
+  /// Whether a single step from `from` to the adjacent `to` is allowed. Ledges can't be climbed, so
+  /// stepping northwards onto or off one is not.
+  pub fn can_step(&self, from: &Pos, to: &Pos) -> bool {
+    let northwards = to.1 < from.1;
+    self.is_walkable(to) && !(northwards && (self.is_ledge(from) || self.is_ledge(to)))
+  }
+
+  pub fn successors(&self, pos: &Pos) -> Vec<(Pos, u32)> {
+    vec![
+      Pos(pos.0 - 1, pos.1),
+      Pos(pos.0 + 1, pos.1),
+      Pos(pos.0, pos.1 - 1),
+      Pos(pos.0, pos.1 + 1),
+    ]
+    .into_iter()
+    .filter(|p| self.can_step(pos, p))
+    .map(|p| {
+      let cost = self.terrain_at(&p).map_or(1, |kind| kind.path_cost());
+      (p, cost)
+    })
+    .collect()
+  }
+
+  /// Runs A* from `start` to `goal`, returning every tile of the path including both ends.
+  pub fn find_path(&self, start: &Pos, goal: &Pos) -> Option<Vec<Pos>> {
+    astar(
+      start,
+      |p| self.successors(p),
+      |p| p.manhattan_distance(goal),
+      |p| p == goal,
+    )
+    .map(|(path, _)| path)
+  }
+
+  /// Whether nothing opaque stands between two translations of the loaded level, checked every
+  /// quarter of a tile along the line.
+  pub fn has_line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+    let steps = (from.distance(to) / (TILE_SIZE as f32 / 4.)).ceil().max(1.) as usize;
+
+    (0..=steps)
+      .map(|step| from.lerp(to, step as f32 / steps as f32))
+      .all(|point| !self.opaque.contains(&self.tile_at(point)))
+  }
+
+  /// Returns the tile under a translation of the loaded level.
+  pub fn tile_at(&self, translation: Vec2) -> Pos {
+    let x = (translation.x / TILE_SIZE as f32).floor() as i32;
+    let y = (translation.y / TILE_SIZE as f32).floor() as i32;
+    Pos(x, self.height - y - 1)
+  }
+
+  pub fn tile_center(&self, pos: &Pos) -> Vec2 {
+    let grid_coords = self.to_grid_coords(pos);
+    Vec2::new(grid_coords.x as f32 + 0.5, grid_coords.y as f32 + 0.5) * TILE_SIZE as f32
+  }
+
+  /// Finds the walkable tile closest to `pos`, searching outwards in steps of one tile.
+  pub fn nearest_walkable(&self, pos: &Pos) -> Option<Pos> {
+    let mut visited = HashSet::from([pos.clone()]);
+    let mut queue = VecDeque::from([pos.clone()]);
+
+    while let Some(current) = queue.pop_front() {
+      if self.is_walkable(&current) {
+        return Some(current);
+      }
+
+      for next in [
+        Pos(current.0 - 1, current.1),
+        Pos(current.0 + 1, current.1),
+        Pos(current.0, current.1 - 1),
+        Pos(current.0, current.1 + 1),
+      ] {
+        if self.in_bounds(&next) && visited.insert(next.clone()) {
+          queue.push_back(next);
+        }
+      }
+    }
+
+    None
+  }
+
+  /// Converts an LDtk tile position into the bottom-up [`GridCoords`] used by Bevy.
+  pub fn to_grid_coords(&self, pos: &Pos) -> GridCoords {
+    GridCoords {
+      x: pos.0,
+      y: self.height - pos.1 - 1,
+    }
+  }
+}
+
+/// Sent when tiles of the [`WalkabilityGrid`] become blocked or free at runtime.
+#[derive(Clone, Debug)]
+pub struct NavigationChanged {
+  pub tiles: Vec<Pos>,
+}
+
+/// The path an entity is walking through the loaded level, kept between frames and only planned
+/// again when its goal moves, the entity strays from it or the tiles it crosses change. A goal no
+/// path leads to is remembered as well, until any tile of the grid changes.
+#[derive(Component, Clone, Debug, Default)]
+pub struct NavPath {
+  pub level_iid: Option<String>,
+  pub goal: Option<Pos>,
+  /// The remaining tiles, starting with the one the entity is on.
+  pub tiles: Vec<Pos>,
+  /// Whether the last planning found no way to `goal`.
+  pub unreachable: bool,
+}
+
+impl NavPath {
+  pub fn leads_to(&self, grid: &WalkabilityGrid, from: &Pos, goal: &Pos) -> bool {
+    self.level_iid == grid.level_iid
+      && self.goal.as_ref() == Some(goal)
+      && (self.unreachable || self.tiles.contains(from))
+  }
+
+  pub fn plan(&mut self, grid: &WalkabilityGrid, from: &Pos, goal: &Pos) {
+    self.level_iid = grid.level_iid.clone();
+    self.goal = Some(goal.clone());
+    match grid.find_path(from, goal) {
+      Some(tiles) => {
+        self.tiles = tiles;
+        self.unreachable = false;
+      }
+      None => {
+        self.tiles.clear();
+        self.unreachable = true;
+      }
+    }
+  }
+
+  pub fn crosses(&self, tiles: &[Pos]) -> bool {
+    self.tiles.iter().any(|tile| tiles.contains(tile))
+  }
+
+  pub fn clear(&mut self) {
+    *self = NavPath::default();
+  }
+
+  /// Forgets the tiles walked before `from` and returns the next one to walk to.
+  pub fn advance(&mut self, from: &Pos) -> Option<&Pos> {
+    if let Some(index) = self.tiles.iter().position(|tile| tile == from) {
+      self.tiles.drain(..index);
+    }
+    self.tiles.get(1)
+  }
+}
+
+/// A level of the LDtk world, laid out in world pixels with `y` growing downwards as in LDtk.
+#[derive(Clone, Debug)]
+pub struct LevelNode {
+  pub iid: String,
+  pub identifier: String,
+  pub layout: Rect,
+  pub neighbours: Vec<String>,
+}
+
+impl LevelNode {
+  pub fn size(&self) -> Vec2 {
+    self.layout.size()
+  }
+
+  /// Converts a translation local to this level (levels spawn at the origin) into world layout
+  /// coordinates.
+  pub fn to_layout(&self, local: Vec2) -> Vec2 {
+    Vec2::new(self.layout.min.x + local.x, self.layout.max.y - local.y)
+  }
+
+  pub fn to_local(&self, layout: Vec2) -> Vec2 {
+    Vec2::new(layout.x - self.layout.min.x, self.layout.max.y - layout.y)
+  }
+}
+
+/// How the levels of the world connect, built from the neighbours LDtk computes for each level.
+/// Used to chase the player across levels that aren't loaded.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LevelGraph {
+  pub levels: HashMap<String, LevelNode>,
+}
+
+impl LevelGraph {
+  pub fn from_levels<'a>(levels: impl IntoIterator<Item = &'a Level>) -> Self {
+    let levels = levels
+      .into_iter()
+      .map(|level| {
+        let min = Vec2::new(level.world_x as f32, level.world_y as f32);
+        let node = LevelNode {
+          iid: level.iid.clone(),
+          identifier: level.identifier.clone(),
+          layout: Rect::from_corners(
+            min,
+            min + Vec2::new(level.px_wid as f32, level.px_hei as f32),
+          ),
+          // Only levels sharing a border can be walked into, not the ones above or below
+          neighbours: level
+            .neighbours
+            .iter()
+            .filter(|neighbour| matches!(neighbour.dir.as_str(), "n" | "s" | "e" | "w"))
+            .map(|neighbour| neighbour.level_iid.clone())
+            .collect(),
+        };
+        (level.iid.clone(), node)
+      })
+      .collect();
+
+    LevelGraph { levels }
+  }
+
+  /// Returns the levels to walk through from `start` to `goal`, including both ends.
+  pub fn route(&self, start: &str, goal: &str) -> Option<Vec<String>> {
+    let mut came_from: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(current) = queue.pop_front() {
+      if current == goal {
+        let mut route = vec![goal.to_string()];
+        let mut step = goal;
+        while let Some(&previous) = came_from.get(step) {
+          route.push(previous.to_string());
+          step = previous;
+        }
+        route.reverse();
+        return Some(route);
+      }
+
+      for neighbour in self
+        .levels
+        .get(current)
+        .into_iter()
+        .flat_map(|node| &node.neighbours)
+      {
+        if neighbour != start && !came_from.contains_key(neighbour.as_str()) {
+          came_from.insert(neighbour, current);
+          queue.push_back(neighbour);
+        }
+      }
+    }
+
+    None
+  }
+
+  /// Where to leave `from` to walk into its neighbour `to`, and where that lands in `to`, both as
+  /// translations local to their level.
+  pub fn crossing(&self, from: &str, to: &str) -> Option<(Vec2, Vec2)> {
+    let from = self.levels.get(from)?;
+    let to = self.levels.get(to)?;
+
+    let exit = to.layout.center().clamp(from.layout.min, from.layout.max);
+    let border = exit.clamp(to.layout.min, to.layout.max);
+    let entry = border + (to.layout.center() - border).normalize_or_zero() * TILE_SIZE as f32 / 2.;
+
+    Some((from.to_local(exit), to.to_local(entry)))
+  }
+}
+
+/// The level an entity that outlives level changes (see [`Worldly`]) currently belongs to.
+#[derive(Component, Clone, Debug, Default, Eq, PartialEq)]
+pub struct InLevel(pub String);
+
+/// Marks an entity whose level isn't the loaded one. It's hidden and doesn't collide, and only
+/// moves through the coarse off-screen simulation.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Offscreen;
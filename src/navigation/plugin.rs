@@ -0,0 +1,19 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::{systems::build_level_graph, LevelGraph, NavigationChanged, WalkabilityGrid};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<WalkabilityGrid>()
+      .init_resource::<LevelGraph>()
+      .add_event::<NavigationChanged>()
+      .add_enter_system(GameState::LevelLoading, build_level_graph)
+      .add_system_set(super::systems::add_systems());
+  }
+}
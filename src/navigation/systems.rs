@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::IntoConditionalSystem;
+use iyes_progress::prelude::*;
+
+use crate::{map::MapAssets, utils::ldtk::selected_level, GameState};
+
+use super::{InLevel, LevelGraph, NavPath, NavigationChanged, Offscreen, WalkabilityGrid};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("navigation")
+    .with_system(bake_walkability_grid.label("navigation-bake-grid"))
+    .with_system(
+      walkability_grid_progress
+        .track_progress()
+        .run_in_state(GameState::LevelLoading)
+        .label("navigation-grid-progress")
+        .after("navigation-bake-grid"),
+    )
+    .with_system(
+      invalidate_changed_paths
+        .label("navigation-invalidate-paths")
+        .after("navigation-bake-grid"),
+    )
+    .with_system(
+      update_offscreen_entities
+        .label("navigation-offscreen")
+        .after("navigation-bake-grid"),
+    )
+}
+
+pub fn build_level_graph(
+  mut commands: Commands,
+  map_assets: Res<MapAssets>,
+  ldtk_assets: Res<Assets<LdtkAsset>>,
+) {
+  if let Some(ldtk_asset) = ldtk_assets.get(&map_assets.map) {
+    commands.insert_resource(LevelGraph::from_levels(&ldtk_asset.project.levels));
+  }
+}
+
+/// Bakes the grid of the selected level whenever it's not the one currently baked.
+fn bake_walkability_grid(
+  mut grid: ResMut<WalkabilityGrid>,
+  level_selection: Res<LevelSelection>,
+  level_query: Query<&Handle<LdtkLevel>>,
+  ldtk_levels: Res<Assets<LdtkLevel>>,
+) {
+  if let Some(ldtk_level) = selected_level(&level_selection, level_query.iter(), &ldtk_levels) {
+    if !grid.is_baked_for(&ldtk_level.level) {
+      *grid = WalkabilityGrid::from_level(&ldtk_level.level);
+    }
+  }
+}
+
+/// Drops the planned paths crossing tiles that were blocked or freed, so they get planned again.
+fn invalidate_changed_paths(
+  mut navigation_events: EventReader<NavigationChanged>,
+  mut paths: Query<&mut NavPath>,
+) {
+  for NavigationChanged { tiles } in navigation_events.iter() {
+    for mut path in paths.iter_mut() {
+      // Any change may have opened a way to a goal that couldn't be reached
+      if path.unreachable || path.crosses(tiles) {
+        path.clear();
+      }
+    }
+  }
+}
+
+fn walkability_grid_progress(
+  grid: Res<WalkabilityGrid>,
+  level_selection: Res<LevelSelection>,
+  level_query: Query<&Handle<LdtkLevel>>,
+  ldtk_levels: Res<Assets<LdtkLevel>>,
+) -> Progress {
+  selected_level(&level_selection, level_query.iter(), &ldtk_levels)
+    .map(|ldtk_level| grid.is_baked_for(&ldtk_level.level))
+    .unwrap_or(false)
+    .into()
+}
+
+/// Hides entities whose level isn't the loaded one and takes them out of the physics world, since
+/// every level spawns at the origin. Entities walking into the loaded level are put back on the
+/// closest walkable tile.
+fn update_offscreen_entities(
+  mut commands: Commands,
+  grid: Res<WalkabilityGrid>,
+  mut entities: Query<(
+    Entity,
+    &InLevel,
+    Option<&Offscreen>,
+    &mut Visibility,
+    &mut Transform,
+  )>,
+) {
+  let Some(loaded_level) = &grid.level_iid else {
+    return;
+  };
+
+  for (entity, InLevel(level_iid), offscreen, mut visibility, mut transform) in entities.iter_mut()
+  {
+    match (level_iid == loaded_level, offscreen.is_some()) {
+      (false, false) => {
+        visibility.is_visible = false;
+        commands
+          .entity(entity)
+          .insert((Offscreen, CollisionGroups::new(Group::NONE, Group::NONE)));
+      }
+      (true, true) => {
+        visibility.is_visible = true;
+        commands
+          .entity(entity)
+          .remove::<Offscreen>()
+          .remove::<CollisionGroups>();
+
+        let tile = grid.tile_at(transform.translation.truncate());
+        if let Some(tile) = grid.nearest_walkable(&tile) {
+          let z = transform.translation.z;
+          transform.translation = grid.tile_center(&tile).extend(z);
+        }
+      }
+      _ => {}
+    }
+  }
+}
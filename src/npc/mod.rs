@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+  animation::{Animator, Facing},
+  blackboard::Blackboard,
+  clock::GameClock,
+  dialogue::Talker,
+  interaction::Interactable,
+  map::ColliderBundle,
+  navigation::NavPath,
+  shop::Shopkeeper,
+  utils::{ldtk::*, position::Pos},
+};
+
+pub mod plugin;
+pub mod systems;
+
+/// Pixels per second NPCs walk at between the places of their [`Schedule`].
+pub const NPC_SPEED: f32 = 40.;
+
+/// How many tiles away from its place a wandering NPC strays.
+pub const WANDER_RADIUS: i32 = 3;
+
+/// Seconds a wandering NPC waits before picking another spot.
+pub const WANDER_PAUSE: f32 = 4.;
+
+/// A non-hostile character placed in LDtk, like the King or the Merchant.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Npc {
+  /// From the `Name` field, falling back to the entity's identifier.
+  pub name: String,
+}
+
+impl From<EntityInstance> for Npc {
+  fn from(entity_instance: EntityInstance) -> Npc {
+    Npc {
+      name: field_string(&entity_instance, "Name")
+        .unwrap_or_else(|| entity_instance.identifier.clone()),
+    }
+  }
+}
+
+/// What an NPC does once it has reached the place of a [`ScheduleEntry`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Activity {
+  #[default]
+  Idle,
+  Work,
+  Sleep,
+  /// Strolls around the place.
+  Wander,
+}
+
+impl std::str::FromStr for Activity {
+  type Err = String;
+
+  fn from_str(activity: &str) -> Result<Self, Self::Err> {
+    match activity.trim().to_lowercase().as_str() {
+      "idle" => Ok(Activity::Idle),
+      "work" => Ok(Activity::Work),
+      "sleep" => Ok(Activity::Sleep),
+      "wander" => Ok(Activity::Wander),
+      _ => Err(format!("unknown activity {activity:?}")),
+    }
+  }
+}
+
+/// Where an NPC should be from hour `from` to hour `to`, wrapping past midnight when `to` comes
+/// first. `location` is the name of a [`Waypoint`] of the NPC's level.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduleEntry {
+  pub from: f32,
+  pub to: f32,
+  pub location: String,
+  pub activity: Activity,
+}
+
+impl std::str::FromStr for ScheduleEntry {
+  type Err = String;
+
+  /// Parses the `from-to location` or `from-to location:activity` notation used by LDtk fields,
+  /// e.g. `8-20 forge:work`.
+  fn from_str(entry: &str) -> Result<Self, Self::Err> {
+    let (hours, place) = entry
+      .trim()
+      .split_once(' ')
+      .ok_or_else(|| "expected `from-to location`".to_string())?;
+    let (from, to) = hours
+      .split_once('-')
+      .ok_or_else(|| "expected hours as `from-to`".to_string())?;
+
+    let (location, activity) = match place.split_once(':') {
+      Some((location, activity)) => (location, activity.parse()?),
+      None => (place, Activity::Idle),
+    };
+
+    Ok(Self {
+      from: from.trim().parse::<f32>().map_err(|e| e.to_string())?,
+      to: to.trim().parse::<f32>().map_err(|e| e.to_string())?,
+      location: location.trim().to_string(),
+      activity,
+    })
+  }
+}
+
+/// The daily routine of an NPC, from its `Schedule` field. The first entry covering the time of day
+/// applies; outside all of them the NPC stays where it is.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Schedule(pub Vec<ScheduleEntry>);
+
+impl Schedule {
+  pub fn current(&self, clock: &GameClock) -> Option<&ScheduleEntry> {
+    self
+      .0
+      .iter()
+      .find(|entry| clock.is_between(entry.from, entry.to))
+  }
+}
+
+impl From<EntityInstance> for Schedule {
+  fn from(entity_instance: EntityInstance) -> Schedule {
+    Schedule(
+      field_strings(&entity_instance, "Schedule")
+        .iter()
+        .filter_map(|entry| match entry.parse::<ScheduleEntry>() {
+          Ok(entry) => Some(entry),
+          Err(error) => {
+            warn!("Invalid schedule entry {entry:?}: {error}");
+            None
+          }
+        })
+        .collect(),
+    )
+  }
+}
+
+/// How far an NPC has got with the current entry of its [`Schedule`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct ScheduleState {
+  /// What the NPC is doing right now, [`Activity::Idle`] while on its way.
+  pub activity: Activity,
+  /// The tile the NPC is walking to or standing on, around the place of the [`ScheduleEntry`]
+  /// whose `location` it's kept with.
+  destination: Option<(String, Pos)>,
+  wait: f32,
+  /// A destination no path leads to, not tried again until the walkable tiles change.
+  unreachable: Option<Pos>,
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct NpcBundle {
+  #[from_entity_instance]
+  pub npc: Npc,
+  #[from_entity_instance]
+  pub talker: Talker,
+  #[from_entity_instance]
+  pub shopkeeper: Shopkeeper,
+  #[from_entity_instance]
+  pub interactable: Interactable,
+  #[from_entity_instance]
+  pub schedule: Schedule,
+  pub schedule_state: ScheduleState,
+  pub blackboard: Blackboard,
+
+  #[from_entity_instance]
+  #[bundle]
+  pub collider_bundle: ColliderBundle,
+  pub controller: KinematicCharacterController,
+  pub nav_path: NavPath,
+  pub animator: Animator,
+  pub facing: Facing,
+
+  #[sprite_sheet_bundle]
+  #[bundle]
+  sprite_bundle: SpriteSheetBundle,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
+
+/// A named spot of a level that NPC schedules send them to.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Waypoint {
+  pub name: String,
+}
+
+impl From<EntityInstance> for Waypoint {
+  fn from(entity_instance: EntityInstance) -> Waypoint {
+    Waypoint {
+      name: field_string(&entity_instance, "Name").unwrap_or_default(),
+    }
+  }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct WaypointBundle {
+  #[from_entity_instance]
+  pub waypoint: Waypoint,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
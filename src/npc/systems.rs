@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  clock::GameClock,
+  navigation::{NavPath, NavigationChanged, WalkabilityGrid},
+  utils::position::Pos,
+  GameState,
+};
+
+use super::{Activity, Schedule, ScheduleState, Waypoint, NPC_SPEED, WANDER_PAUSE, WANDER_RADIUS};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new().label("npc").with_system(
+    follow_schedules
+      .run_in_state(GameState::Playing)
+      .label("npc-follow-schedules")
+      .after("clock-advance"),
+  )
+}
+
+/// Walks every NPC to the [`Waypoint`] its [`Schedule`] names for the time of day and starts the
+/// activity once there. Wandering NPCs keep picking walkable tiles around the waypoint, pausing at
+/// each. NPCs that can't get where they are going wait until the walkable tiles change.
+fn follow_schedules(
+  mut navigation_events: EventReader<NavigationChanged>,
+  mut npcs: Query<(
+    &Schedule,
+    &mut ScheduleState,
+    &mut NavPath,
+    &mut KinematicCharacterController,
+    &Transform,
+  )>,
+  waypoints: Query<(&Waypoint, &Transform)>,
+  grid: Res<WalkabilityGrid>,
+  clock: Res<GameClock>,
+  time: Res<Time>,
+) {
+  if grid.level_iid.is_none() {
+    return;
+  }
+
+  let navigation_changed = navigation_events.iter().count() > 0;
+
+  for (schedule, mut state, mut nav_path, mut controller, transform) in npcs.iter_mut() {
+    if navigation_changed && state.unreachable.is_some() {
+      state.unreachable = None;
+    }
+
+    let Some(entry) = schedule.current(&clock) else {
+      nav_path.clear();
+      continue;
+    };
+
+    // Waypoints of other levels aren't spawned, so those NPCs wait where they are
+    let Some(place) = waypoints
+      .iter()
+      .find(|(waypoint, _)| waypoint.name == entry.location)
+      .and_then(|(_, waypoint_transform)| {
+        grid.nearest_walkable(&grid.tile_at(waypoint_transform.translation.truncate()))
+      })
+    else {
+      continue;
+    };
+
+    let position = transform.translation.truncate();
+    let tile = grid.tile_at(position);
+
+    let destination = match &state.destination {
+      // Wander spots only hold while the NPC is still wandering around the same place
+      Some((location, destination))
+        if *location == entry.location
+          && (entry.activity == Activity::Wander || *destination == place) =>
+      {
+        destination.clone()
+      }
+      _ => place.clone(),
+    };
+
+    if tile == destination {
+      // Kept until the pause is over, so wandering NPCs wait where they strayed to
+      state.activity = entry.activity;
+      state.destination = Some((entry.location.clone(), destination));
+
+      if entry.activity == Activity::Wander {
+        state.wait -= time.delta_seconds();
+        if state.wait <= 0. {
+          state.wait = WANDER_PAUSE * (0.5 + fastrand::f32());
+          state.destination = wander_destination(&grid, &place)
+            .map(|destination| (entry.location.clone(), destination));
+        }
+      }
+      continue;
+    }
+
+    state.destination = Some((entry.location.clone(), destination.clone()));
+    if tile.manhattan_distance(&place) > WANDER_RADIUS as u32 {
+      state.activity = Activity::Idle;
+    }
+
+    if state.unreachable.as_ref() == Some(&destination) {
+      // A wander spot that can't be reached is traded for the place itself
+      if destination != place {
+        state.destination = None;
+      }
+      continue;
+    }
+
+    if !nav_path.leads_to(&grid, &tile, &destination) {
+      nav_path.plan(&grid, &tile, &destination);
+    }
+
+    let Some(next_tile) = nav_path.advance(&tile) else {
+      // Unreachable, give up until the schedule moves on or the way opens
+      state.unreachable = Some(destination);
+      nav_path.clear();
+      continue;
+    };
+
+    let desired_translation = (grid.tile_center(next_tile) - position).normalize_or_zero()
+      * time.delta_seconds()
+      * NPC_SPEED;
+
+    controller.translation = match controller.translation {
+      Some(translation) => Some(translation + desired_translation),
+      None => Some(desired_translation),
+    };
+  }
+}
+
+/// A random walkable tile at most [`WANDER_RADIUS`] tiles away from `place`.
+fn wander_destination(grid: &WalkabilityGrid, place: &Pos) -> Option<Pos> {
+  let offset = || fastrand::i32(-WANDER_RADIUS..=WANDER_RADIUS);
+
+  (0..8)
+    .map(|_| Pos(place.0 + offset(), place.1 + offset()))
+    .find(|tile| grid.is_walkable(tile) && grid.find_path(place, tile).is_some())
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{interaction::Interactable, map::ColliderBundle, utils::ldtk::*};
+
+pub mod plugin;
+pub mod systems;
+
+/// How long the player has to keep walking into a rock before it moves a tile, in seconds.
+pub const PUSH_DELAY: f32 = 0.25;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ObstacleKind {
+  /// Opened and closed through [`SetObstacleBlocking`], e.g. by a lever.
+  #[default]
+  Gate,
+  /// Pushed a tile at a time by the player.
+  Rock,
+  /// Destroyed once its health runs out.
+  Crate,
+}
+
+/// Something in a level that blocks the tile it stands on only some of the time. While blocking it
+/// has a collider and its tile is blocked in the [`WalkabilityGrid`](crate::navigation::WalkabilityGrid).
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Obstacle {
+  pub kind: ObstacleKind,
+  pub blocking: bool,
+}
+
+impl From<EntityInstance> for Obstacle {
+  fn from(entity_instance: EntityInstance) -> Obstacle {
+    match entity_instance.identifier.as_ref() {
+      "Gate" => Obstacle {
+        kind: ObstacleKind::Gate,
+        blocking: !field_bool(&entity_instance, "Open").unwrap_or(false),
+      },
+      "Rock" => Obstacle {
+        kind: ObstacleKind::Rock,
+        blocking: true,
+      },
+      "Crate" => Obstacle {
+        kind: ObstacleKind::Crate,
+        blocking: true,
+      },
+      _ => Obstacle::default(),
+    }
+  }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct ObstacleBundle {
+  #[from_entity_instance]
+  pub obstacle: Obstacle,
+
+  #[from_entity_instance]
+  #[bundle]
+  pub collider_bundle: ColliderBundle,
+
+  #[sprite_sheet_bundle]
+  #[bundle]
+  sprite_bundle: SpriteSheetBundle,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
+
+/// Toggles every gate whose `Channel` field matches its own when pulled.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Lever {
+  pub channel: String,
+  pub pulled: bool,
+}
+
+impl From<EntityInstance> for Lever {
+  fn from(entity_instance: EntityInstance) -> Lever {
+    Lever {
+      channel: field_string(&entity_instance, "Channel").unwrap_or_default(),
+      pulled: false,
+    }
+  }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct LeverBundle {
+  #[from_entity_instance]
+  pub lever: Lever,
+  #[from_entity_instance]
+  pub interactable: Interactable,
+
+  #[sprite_sheet_bundle]
+  #[bundle]
+  sprite_bundle: SpriteSheetBundle,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
+
+/// Opens (`blocking: false`) or closes an [`Obstacle`].
+#[derive(Clone, Copy, Debug)]
+pub struct SetObstacleBlocking {
+  pub obstacle: Entity,
+  pub blocking: bool,
+}
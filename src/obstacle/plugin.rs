@@ -0,0 +1,13 @@
+use bevy::prelude::{App, Plugin};
+
+use super::SetObstacleBlocking;
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_event::<SetObstacleBlocking>()
+      .add_system_set(super::systems::add_systems());
+  }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::IntoConditionalSystem;
+
+use crate::{
+  combat::Health,
+  interaction::InteractEvent,
+  navigation::{NavigationChanged, WalkabilityGrid, TILE_SIZE},
+  player::Player,
+  utils::{
+    ldtk::{field_float, field_string},
+    position::Pos,
+  },
+  GameState,
+};
+
+use super::{Lever, Obstacle, ObstacleKind, SetObstacleBlocking, PUSH_DELAY};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("obstacle")
+    .with_system(spawn_crate_health.label("obstacle-spawn-crate-health"))
+    .with_system(
+      pull_levers
+        .run_in_state(GameState::Playing)
+        .label("obstacle-pull-levers")
+        .after("interaction-interact"),
+    )
+    .with_system(
+      set_obstacle_blocking
+        .label("obstacle-set-blocking")
+        .after("obstacle-pull-levers"),
+    )
+    .with_system(
+      push_rocks
+        .run_in_state(GameState::Playing)
+        .label("obstacle-push-rocks")
+        .after("player-movement"),
+    )
+    .with_system(
+      sync_obstacle_colliders
+        .label("obstacle-sync-colliders")
+        .after("obstacle-set-blocking"),
+    )
+    .with_system(
+      update_obstacle_tiles
+        .label("obstacle-update-tiles")
+        .after("obstacle-set-blocking")
+        .after("navigation-bake-grid")
+        .before("navigation-invalidate-paths"),
+    )
+}
+
+/// Crates can be destroyed, so they get the [`Health`] set in their `Health` field.
+fn spawn_crate_health(
+  mut commands: Commands,
+  obstacles: Query<(Entity, &Obstacle, &EntityInstance), Added<Obstacle>>,
+) {
+  for (entity, obstacle, entity_instance) in obstacles.iter() {
+    if obstacle.kind == ObstacleKind::Crate {
+      let health = field_float(entity_instance, "Health").unwrap_or(3.);
+      commands.entity(entity).insert(Health::new(health));
+    }
+  }
+}
+
+/// Flips the levers the player interacts with, and the gates on their channel along with them.
+fn pull_levers(
+  mut interact_events: EventReader<InteractEvent>,
+  mut levers: Query<(&mut Lever, Option<&mut TextureAtlasSprite>)>,
+  obstacles: Query<(Entity, &Obstacle, &EntityInstance)>,
+  mut blocking_events: EventWriter<SetObstacleBlocking>,
+) {
+  for &InteractEvent { target, .. } in interact_events.iter() {
+    let Ok((mut lever, sprite)) = levers.get_mut(target) else {
+      continue;
+    };
+
+    lever.pulled = !lever.pulled;
+    if let Some(mut sprite) = sprite {
+      sprite.flip_x = lever.pulled;
+    }
+
+    for (entity, obstacle, entity_instance) in obstacles.iter() {
+      let on_channel =
+        field_string(entity_instance, "Channel").as_deref() == Some(lever.channel.as_str());
+      if obstacle.kind == ObstacleKind::Gate && on_channel {
+        blocking_events.send(SetObstacleBlocking {
+          obstacle: entity,
+          blocking: !obstacle.blocking,
+        });
+      }
+    }
+  }
+}
+
+fn set_obstacle_blocking(
+  mut events: EventReader<SetObstacleBlocking>,
+  mut obstacles: Query<&mut Obstacle>,
+) {
+  for &SetObstacleBlocking {
+    obstacle: entity,
+    blocking,
+  } in events.iter()
+  {
+    if let Ok(mut obstacle) = obstacles.get_mut(entity) {
+      if obstacle.blocking != blocking {
+        obstacle.blocking = blocking;
+      }
+    }
+  }
+}
+
+/// Gives blocking obstacles a collider and takes it away from the others. Open obstacles are drawn
+/// faded.
+fn sync_obstacle_colliders(
+  mut commands: Commands,
+  mut obstacles: Query<(Entity, &Obstacle, Option<&mut TextureAtlasSprite>), Changed<Obstacle>>,
+) {
+  for (entity, obstacle, sprite) in obstacles.iter_mut() {
+    if obstacle.blocking {
+      let half_tile = TILE_SIZE as f32 / 2.;
+      commands
+        .entity(entity)
+        .insert(Collider::cuboid(half_tile, half_tile));
+    } else {
+      commands.entity(entity).remove::<Collider>();
+    }
+
+    if let Some(mut sprite) = sprite {
+      sprite.color.set_a(if obstacle.blocking { 1. } else { 0.3 });
+    }
+  }
+}
+
+/// Moves a rock one tile away from the player once they have walked into it for [`PUSH_DELAY`]
+/// seconds, as long as the tile behind it is free.
+fn push_rocks(
+  players: Query<(&KinematicCharacterController, &GlobalTransform), With<Player>>,
+  mut obstacles: Query<(Entity, &Obstacle, &GlobalTransform, &mut Transform), Without<Player>>,
+  grid: Res<WalkabilityGrid>,
+  time: Res<Time>,
+  mut pushing: Local<Option<(Entity, f32)>>,
+) {
+  let Ok((controller, player_transform)) = players.get_single() else {
+    return;
+  };
+
+  let direction = controller.translation.unwrap_or(Vec2::ZERO);
+
+  // Rocks only move along one axis, and `Pos` grows downwards
+  let (step, offset) = if direction.x.abs() > direction.y.abs() {
+    (
+      Pos(direction.x.signum() as i32, 0),
+      Vec2::X * direction.x.signum(),
+    )
+  } else if direction.y != 0. {
+    (
+      Pos(0, -direction.y.signum() as i32),
+      Vec2::Y * direction.y.signum(),
+    )
+  } else {
+    *pushing = None;
+    return;
+  };
+
+  let player_tile = grid.tile_at(player_transform.translation().truncate());
+  let rock_tile = Pos(player_tile.0 + step.0, player_tile.1 + step.1);
+  let destination = Pos(rock_tile.0 + step.0, rock_tile.1 + step.1);
+
+  let Some((rock, _, _, mut transform)) =
+    obstacles
+      .iter_mut()
+      .find(|(_, obstacle, global_transform, _)| {
+        obstacle.kind == ObstacleKind::Rock
+          && obstacle.blocking
+          && grid.tile_at(global_transform.translation().truncate()) == rock_tile
+      })
+  else {
+    *pushing = None;
+    return;
+  };
+
+  if !grid.is_walkable(&destination) {
+    *pushing = None;
+    return;
+  }
+
+  let pushed_for = match *pushing {
+    Some((entity, pushed_for)) if entity == rock => pushed_for + time.delta_seconds(),
+    _ => time.delta_seconds(),
+  };
+
+  if pushed_for >= PUSH_DELAY {
+    transform.translation += (offset * TILE_SIZE as f32).extend(0.);
+    *pushing = None;
+  } else {
+    *pushing = Some((rock, pushed_for));
+  }
+}
+
+/// Blocks the tiles of the loaded level under blocking obstacles in the [`WalkabilityGrid`] and
+/// frees the others, announcing the tiles that changed.
+fn update_obstacle_tiles(
+  mut grid: ResMut<WalkabilityGrid>,
+  obstacles: Query<(&Obstacle, &GlobalTransform)>,
+  mut navigation_events: EventWriter<NavigationChanged>,
+) {
+  if grid.level_iid.is_none() {
+    return;
+  }
+
+  let blocked: HashSet<Pos> = obstacles
+    .iter()
+    .filter(|(obstacle, _)| obstacle.blocking)
+    .map(|(_, transform)| grid.tile_at(transform.translation().truncate()))
+    .collect();
+
+  if blocked == grid.blocked {
+    return;
+  }
+
+  let tiles = blocked
+    .symmetric_difference(&grid.blocked)
+    .cloned()
+    .collect();
+  grid.blocked = blocked;
+  navigation_events.send(NavigationChanged { tiles });
+}
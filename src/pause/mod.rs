@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::camera::CameraMode;
+
+pub mod plugin;
+pub mod systems;
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+  pub fullscreen: bool,
+  pub screen_shake: bool,
+  pub camera_mode: CameraMode,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      fullscreen: false,
+      screen_shake: true,
+      camera_mode: CameraMode::default(),
+    }
+  }
+}
+
+/// Which page of the pause menu is currently shown.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PauseMenuPage {
+  #[default]
+  Main,
+  Settings,
+  Save,
+}
+
+#[derive(Component)]
+pub struct PauseMenu;
+
+#[derive(Component)]
+pub struct PauseMenuPanel;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseButton {
+  Resume,
+  Settings,
+  Save,
+  SaveSlot(usize),
+  Quit,
+  ToggleFullscreen,
+  ToggleScreenShake,
+  CycleCameraMode,
+  Back,
+}
@@ -0,0 +1,24 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::{
+  systems::{despawn_pause_menu, freeze_gameplay, resume_gameplay, spawn_pause_menu},
+  PauseMenuPage, Settings,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<Settings>()
+      .init_resource::<PauseMenuPage>()
+      .add_enter_system(GameState::Paused, spawn_pause_menu)
+      .add_enter_system(GameState::Paused, freeze_gameplay)
+      .add_exit_system(GameState::Paused, despawn_pause_menu)
+      .add_exit_system(GameState::Paused, resume_gameplay)
+      .add_system_set(super::systems::add_systems());
+  }
+}
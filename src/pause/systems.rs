@@ -0,0 +1,211 @@
+use bevy::{prelude::*, window::WindowMode};
+use bevy_rapier2d::prelude::RapierConfiguration;
+use iyes_loopless::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+  player::{state_machine::TopDownAction, Player},
+  save::{slot_exists, SaveGame, SAVE_SLOTS},
+  ui::{self, UiAssets},
+  GameState,
+};
+
+use super::{PauseButton, PauseMenu, PauseMenuPage, PauseMenuPanel, Settings};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("pause")
+    .with_system(pause.run_in_state(GameState::Playing).label("pause-pause"))
+    .with_system(
+      unpause
+        .run_in_state(GameState::Paused)
+        .label("pause-unpause"),
+    )
+    .with_system(
+      refresh_pause_menu
+        .run_in_state(GameState::Paused)
+        .label("pause-refresh-menu"),
+    )
+    .with_system(
+      press_pause_buttons
+        .run_in_state(GameState::Paused)
+        .label("pause-buttons"),
+    )
+    .with_system(apply_settings.label("pause-apply-settings"))
+}
+
+/// Stops everything that advances the simulation: the physics pipeline and the game clock, so
+/// timers, movement and the AI triggers that depend on positions all stand still. Input and UI
+/// systems keep running.
+pub fn freeze_gameplay(
+  mut rapier_configuration: ResMut<RapierConfiguration>,
+  mut time: ResMut<Time>,
+) {
+  rapier_configuration.physics_pipeline_active = false;
+  time.pause();
+}
+
+pub fn resume_gameplay(
+  mut rapier_configuration: ResMut<RapierConfiguration>,
+  mut time: ResMut<Time>,
+) {
+  rapier_configuration.physics_pipeline_active = true;
+  time.unpause();
+}
+
+fn pause(mut commands: Commands, players: Query<&ActionState<TopDownAction>, With<Player>>) {
+  if players
+    .iter()
+    .any(|action_state| action_state.just_pressed(TopDownAction::Pause))
+  {
+    commands.insert_resource(PauseMenuPage::Main);
+    commands.insert_resource(NextState(GameState::Paused));
+  }
+}
+
+fn unpause(mut commands: Commands, players: Query<&ActionState<TopDownAction>, With<Player>>) {
+  if players
+    .iter()
+    .any(|action_state| action_state.just_pressed(TopDownAction::Pause))
+  {
+    commands.insert_resource(NextState(GameState::Playing));
+  }
+}
+
+pub fn spawn_pause_menu(mut commands: Commands) {
+  commands
+    .spawn((ui::overlay(Color::rgba(0., 0., 0., 0.6)), PauseMenu))
+    .with_children(|overlay| {
+      overlay.spawn((ui::panel(), PauseMenuPanel));
+    });
+}
+
+pub fn despawn_pause_menu(mut commands: Commands, menus: Query<Entity, With<PauseMenu>>) {
+  for menu in menus.iter() {
+    commands.entity(menu).despawn_recursive();
+  }
+}
+
+/// Fills the panel with the buttons of the current page, rebuilding it whenever the page or the
+/// settings it displays change.
+fn refresh_pause_menu(
+  mut commands: Commands,
+  new_panels: Query<Entity, Added<PauseMenuPanel>>,
+  panels: Query<Entity, With<PauseMenuPanel>>,
+  page: Res<PauseMenuPage>,
+  settings: Res<Settings>,
+  ui_assets: Res<UiAssets>,
+) {
+  let stale_panels: Vec<Entity> = if page.is_changed() || settings.is_changed() {
+    panels.iter().collect()
+  } else {
+    new_panels.iter().collect()
+  };
+
+  let on_off = |value: bool| if value { "on" } else { "off" };
+
+  let (title, buttons) = match *page {
+    PauseMenuPage::Main => (
+      "Paused".to_string(),
+      vec![
+        (PauseButton::Resume, "Resume".to_string()),
+        (PauseButton::Save, "Save game".to_string()),
+        (PauseButton::Settings, "Settings".to_string()),
+        (PauseButton::Quit, "Quit to title".to_string()),
+      ],
+    ),
+    PauseMenuPage::Settings => (
+      "Settings".to_string(),
+      vec![
+        (
+          PauseButton::ToggleFullscreen,
+          format!("Fullscreen: {}", on_off(settings.fullscreen)),
+        ),
+        (
+          PauseButton::ToggleScreenShake,
+          format!("Screen shake: {}", on_off(settings.screen_shake)),
+        ),
+        (
+          PauseButton::CycleCameraMode,
+          format!("Camera: {}", settings.camera_mode.label()),
+        ),
+        (PauseButton::Back, "Back".to_string()),
+      ],
+    ),
+    PauseMenuPage::Save => (
+      "Save game".to_string(),
+      (1..=SAVE_SLOTS)
+        .map(|slot| {
+          let state = if slot_exists(slot) {
+            "overwrite"
+          } else {
+            "empty"
+          };
+          (
+            PauseButton::SaveSlot(slot),
+            format!("Slot {slot} ({state})"),
+          )
+        })
+        .chain(std::iter::once((PauseButton::Back, "Back".to_string())))
+        .collect(),
+    ),
+  };
+
+  for panel in stale_panels {
+    commands.entity(panel).despawn_descendants();
+    commands.entity(panel).with_children(|panel| {
+      panel.spawn(ui_assets.text(title.clone(), 32.));
+
+      for (button, text) in buttons.iter() {
+        panel
+          .spawn((ui::button(), *button))
+          .with_children(|parent| {
+            parent.spawn(ui_assets.text(text.clone(), 20.));
+          });
+      }
+    });
+  }
+}
+
+fn press_pause_buttons(
+  mut commands: Commands,
+  buttons: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+  mut page: ResMut<PauseMenuPage>,
+  mut settings: ResMut<Settings>,
+  mut save_events: EventWriter<SaveGame>,
+) {
+  for (interaction, button) in buttons.iter() {
+    if *interaction != Interaction::Clicked {
+      continue;
+    }
+
+    match button {
+      PauseButton::Resume => commands.insert_resource(NextState(GameState::Playing)),
+      PauseButton::Settings => *page = PauseMenuPage::Settings,
+      PauseButton::Save => *page = PauseMenuPage::Save,
+      PauseButton::SaveSlot(slot) => {
+        save_events.send(SaveGame { slot: *slot });
+        *page = PauseMenuPage::Main;
+      }
+      PauseButton::Quit => commands.insert_resource(NextState(GameState::MainMenu)),
+      PauseButton::ToggleFullscreen => settings.fullscreen = !settings.fullscreen,
+      PauseButton::ToggleScreenShake => settings.screen_shake = !settings.screen_shake,
+      PauseButton::CycleCameraMode => settings.camera_mode = settings.camera_mode.next(),
+      PauseButton::Back => *page = PauseMenuPage::Main,
+    }
+  }
+}
+
+fn apply_settings(settings: Res<Settings>, mut windows: ResMut<Windows>) {
+  if !settings.is_changed() {
+    return;
+  }
+
+  if let Some(window) = windows.get_primary_mut() {
+    window.set_mode(if settings.fullscreen {
+      WindowMode::BorderlessFullscreen
+    } else {
+      WindowMode::Windowed
+    });
+  }
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use leafwing_input_manager::{
   prelude::{ActionState, InputMap, SingleAxis},
+  user_input::{InputKind, UserInput},
   InputManagerBundle,
 };
 
@@ -53,12 +54,21 @@ impl Default for PlayerInput {
     input_map.insert(KeyCode::Space, TopDownAction::Dash);
     input_map.insert(GamepadButtonType::South, TopDownAction::Dash);
 
+    input_map.insert(KeyCode::F, TopDownAction::Shoot);
+    input_map.insert(GamepadButtonType::East, TopDownAction::Shoot);
+
+    input_map.insert(KeyCode::E, TopDownAction::Interact);
+    input_map.insert(GamepadButtonType::West, TopDownAction::Interact);
+
     input_map.insert(KeyCode::Return, TopDownAction::Pause);
     input_map.insert(GamepadButtonType::Start, TopDownAction::Pause);
 
     input_map.insert(KeyCode::I, TopDownAction::Menus);
     input_map.insert(GamepadButtonType::Select, TopDownAction::Menus);
 
+    input_map.insert(KeyCode::J, TopDownAction::Quests);
+    input_map.insert(GamepadButtonType::North, TopDownAction::Quests);
+
     input_map.set_gamepad(Gamepad { id: 0 });
 
     Self {
@@ -97,6 +107,20 @@ pub fn transform_from_action(action_state: &ActionState<TopDownAction>, delta: f
   (x_transform, y_transform)
 }
 
+/// The name of the gamepad button `action` is bound to when `gamepad` is true, or else of its key,
+/// to show in prompts.
+pub fn binding_label(
+  input_map: &InputMap<TopDownAction>,
+  action: TopDownAction,
+  gamepad: bool,
+) -> Option<String> {
+  input_map.get(action).iter().find_map(|input| match input {
+    UserInput::Single(InputKind::GamepadButton(button)) if gamepad => Some(format!("{button:?}")),
+    UserInput::Single(InputKind::Keyboard(key)) if !gamepad => Some(format!("{key:?}")),
+    _ => None,
+  })
+}
+
 /// Simple resource to store the ID of the
 /// connected gamepad. We need to know which
 /// gamepad to use for player input.
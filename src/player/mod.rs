@@ -2,7 +2,15 @@ use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::map::{ColliderBundle, WallDetection};
+use crate::{
+  animation::{Animator, Facing},
+  camera::CameraTarget,
+  combat::{Health, MeleeAttack},
+  faction::Faction,
+  inventory::Inventory,
+  map::{ColliderBundle, WallDetection},
+  shop::Wallet,
+};
 
 use self::controller::PlayerInput;
 
@@ -22,9 +30,18 @@ pub struct PlayerBundle {
   #[bundle]
   pub collider_bundle: ColliderBundle,
   pub wall_detection: WallDetection,
+  pub camera_target: CameraTarget,
+  pub animator: Animator,
+  pub facing: Facing,
 
   pub player: Player,
+  #[from_entity_instance]
+  pub faction: Faction,
   pub controller: KinematicCharacterController,
+  pub health: Health,
+  pub melee_attack: MeleeAttack,
+  pub inventory: Inventory,
+  pub wallet: Wallet,
 
   #[bundle]
   pub input: PlayerInput,
@@ -18,7 +18,11 @@ pub enum TopDownAction {
   Shoot,
   Dash,
 
+  // World actions
+  Interact,
+
   // Menu actions
   Pause,
   Menus,
+  Quests,
 }
@@ -1,10 +1,17 @@
 use bevy::prelude::*;
-use bevy_ecs_ldtk::{utils::translation_to_grid_coords, GridCoords};
+use bevy_ecs_ldtk::GridCoords;
 use bevy_rapier2d::prelude::{KinematicCharacterController, Velocity};
 use iyes_loopless::prelude::IntoConditionalSystem;
 use leafwing_input_manager::prelude::ActionState;
 
-use crate::GameState;
+use crate::{
+  animation::{AnimationState, PlayAnimation},
+  combat::{DamageEvent, Dead, Health, MeleeAttack},
+  faction::Faction,
+  navigation::{Offscreen, WalkabilityGrid, TILE_SIZE},
+  utils::position::Pos,
+  GameState,
+};
 
 use super::{controller::transform_from_action, state_machine::TopDownAction, Player};
 
@@ -16,16 +23,22 @@ pub fn add_systems() -> SystemSet {
         .run_in_state(GameState::Playing)
         .label("player-movement"), // .after("player-spawn"),
     )
+    .with_system(
+      attack
+        .run_in_state(GameState::Playing)
+        .label("player-attack")
+        .before("combat-apply-damage"),
+    )
     .with_system(
       update_grid_coords_from_player
         .run_in_state(GameState::Playing)
         .label("player-grid-coords"), // .after("player-spawn"),
     )
-    // .with_system(
-    //   debug_user_grid_coordinates
-    //     .run_in_state(GameState::Playing)
-    //     .label("player-debug-grid-coords"), // .after("player-spawn"),
-    // )
+  // .with_system(
+  //   debug_user_grid_coordinates
+  //     .run_in_state(GameState::Playing)
+  //     .label("player-debug-grid-coords"), // .after("player-spawn"),
+  // )
 }
 
 pub fn movement(
@@ -33,13 +46,33 @@ pub fn movement(
     (
       &ActionState<TopDownAction>,
       &mut KinematicCharacterController,
+      &Transform,
     ),
     With<Velocity>,
   >,
+  grid: Res<WalkabilityGrid>,
   time: Res<Time>,
 ) {
-  for (action_state, mut controller) in controllers.iter_mut() {
-    let (x_transform, y_transform) = transform_from_action(action_state, time.delta_seconds());
+  for (action_state, mut controller, transform) in controllers.iter_mut() {
+    let (mut x_transform, mut y_transform) =
+      transform_from_action(action_state, time.delta_seconds());
+
+    if grid.level_iid.is_some() {
+      let position = transform.translation.truncate();
+      let tile = grid.tile_at(position);
+
+      let speed = grid
+        .terrain_at(&tile)
+        .map_or(1., |kind| kind.speed_multiplier());
+      x_transform *= speed;
+      y_transform *= speed;
+
+      // Ledges can only be walked down, the player is one tile tall
+      let tile_ahead = grid.tile_at(position + Vec2::new(0., TILE_SIZE as f32 / 2. + y_transform));
+      if y_transform > 0. && (grid.is_ledge(&tile) || grid.is_ledge(&tile_ahead)) {
+        y_transform = 0.;
+      }
+    }
 
     controller.translation = match controller.translation {
       Some(mut v) => {
@@ -52,13 +85,81 @@ pub fn movement(
   }
 }
 
-/// The initial transform of the player is (19.0, 330.0, 3.0) and is equivalent to (0, 0) in grid coordinates.
-fn update_grid_coords_from_player(mut player: Query<(&Transform, &mut GridCoords), With<Player>>) {
+type VictimWhen = (
+  With<Health>,
+  Without<Dead>,
+  Without<Offscreen>,
+  Without<Player>,
+);
+
+/// Hits whoever is closest within reach of the player's [`MeleeAttack`] when `Shoot` is pressed,
+/// sparing the player's own faction.
+fn attack(
+  mut damage_events: EventWriter<DamageEvent>,
+  mut play_events: EventWriter<PlayAnimation>,
+  mut players: Query<
+    (
+      Entity,
+      &ActionState<TopDownAction>,
+      &mut MeleeAttack,
+      &GlobalTransform,
+    ),
+    (With<Player>, Without<Dead>),
+  >,
+  victims: Query<(Entity, &Faction, &GlobalTransform), VictimWhen>,
+  time: Res<Time>,
+) {
+  for (player, action_state, mut melee_attack, transform) in players.iter_mut() {
+    melee_attack.ready_in = (melee_attack.ready_in - time.delta_seconds()).max(0.);
+    if melee_attack.ready_in > 0. || !action_state.just_pressed(TopDownAction::Shoot) {
+      continue;
+    }
+
+    melee_attack.ready_in = melee_attack.cooldown;
+    play_events.send(PlayAnimation {
+      entity: player,
+      state: AnimationState::Attack,
+    });
+
+    let position = transform.translation().truncate();
+    let Some((victim, _)) = victims
+      .iter()
+      .filter(|(_, faction, _)| **faction != Faction::Player)
+      .map(|(victim, _, victim_transform)| {
+        (
+          victim,
+          victim_transform.translation().truncate().distance(position),
+        )
+      })
+      .filter(|(_, distance)| *distance <= melee_attack.range)
+      .min_by(|(_, distance_a), (_, distance_b)| distance_a.total_cmp(distance_b))
+    else {
+      continue;
+    };
+
+    damage_events.send(DamageEvent {
+      target: victim,
+      amount: melee_attack.damage,
+      source: Some(player),
+    });
+  }
+}
+
+/// Keeps the [`GridCoords`] of the player on its tile, counted from the top like the
+/// [`WalkabilityGrid`].
+fn update_grid_coords_from_player(
+  mut player: Query<(&Transform, &mut GridCoords), With<Player>>,
+  grid: Res<WalkabilityGrid>,
+) {
+  if grid.level_iid.is_none() {
+    return;
+  }
+
   for (transform, mut grid_coords) in player.iter_mut() {
-    let grid = translation_to_grid_coords(transform.translation.truncate(), (16, 16).into());
+    let Pos(x, y) = grid.tile_at(transform.translation.truncate());
 
-    grid_coords.x = grid.x;
-    grid_coords.y = 16 - grid.y - 1;
+    grid_coords.x = x;
+    grid_coords.y = y;
   }
 }
 
@@ -0,0 +1,241 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_asset_loader::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{faction::Faction, inventory::ItemDefinitions, utils::ldtk::field_string};
+
+pub mod plugin;
+pub mod screen;
+pub mod systems;
+
+/// Something the player has to do for a quest.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum Objective {
+  /// Defeat `count` enemies of the given [`EnemyArchetype`](crate::enemy::EnemyArchetype), or of
+  /// any kind without one.
+  Kill {
+    #[serde(default)]
+    archetype: Option<String>,
+    count: u32,
+  },
+  /// Walk into the [`QuestArea`] with this name.
+  Reach(String),
+  /// Finish a conversation with the [`Npc`](crate::npc::Npc) with this name.
+  TalkTo(String),
+  /// Carry `amount` of `item` at once.
+  Collect { item: String, amount: u32 },
+}
+
+impl Objective {
+  /// The progress at which the objective is done.
+  pub fn required(&self) -> u32 {
+    match self {
+      Objective::Kill { count, .. } => *count,
+      Objective::Collect { amount, .. } => *amount,
+      Objective::Reach(_) | Objective::TalkTo(_) => 1,
+    }
+  }
+
+  pub fn describe(&self, definitions: Option<&ItemDefinitions>) -> String {
+    match self {
+      Objective::Kill { archetype, count } => {
+        format!(
+          "Defeat {count} {}",
+          archetype.as_deref().unwrap_or("enemies")
+        )
+      }
+      Objective::Reach(area) => format!("Reach {area}"),
+      Objective::TalkTo(npc) => format!("Talk to {npc}"),
+      Objective::Collect { item, amount } => {
+        let name = definitions.map_or(item.as_str(), |definitions| definitions.name(item));
+        format!("Collect {amount} {name}")
+      }
+    }
+  }
+}
+
+/// Given once a quest is completed.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum Reward {
+  Item {
+    item: String,
+    amount: u32,
+  },
+  /// Raises a story flag, for dialogue conditions to check.
+  Flag(String),
+  /// Changes the player's standing with a faction.
+  Reputation {
+    faction: Faction,
+    amount: i32,
+  },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct QuestDefinition {
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  /// Whether the objectives have to be done one after the other rather than in any order.
+  #[serde(default)]
+  pub ordered: bool,
+  pub objectives: Vec<Objective>,
+  #[serde(default)]
+  pub rewards: Vec<Reward>,
+}
+
+/// Every quest of the game keyed by its id, loaded from `assets/quests/game.quests.ron`.
+#[derive(Clone, Debug, Default, Deserialize, TypeUuid)]
+#[serde(transparent)]
+#[uuid = "c3a8e1f4-6b2d-4f7a-8e95-0d4b7c2a1e36"]
+pub struct QuestDefinitions(pub HashMap<String, QuestDefinition>);
+
+impl QuestDefinitions {
+  pub fn get(&self, quest: &str) -> Option<&QuestDefinition> {
+    self.0.get(quest)
+  }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct QuestAssets {
+  #[asset(path = "quests/game.quests.ron")]
+  pub definitions: Handle<QuestDefinitions>,
+}
+
+/// How far the player has got with a quest they are on.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct QuestProgress {
+  pub quest: String,
+  /// The progress of each objective, in the order of the definition.
+  pub objectives: Vec<u32>,
+}
+
+impl QuestProgress {
+  fn get(&self, index: usize) -> u32 {
+    self.objectives.get(index).copied().unwrap_or_default()
+  }
+
+  pub fn is_done(&self, definition: &QuestDefinition, index: usize) -> bool {
+    definition
+      .objectives
+      .get(index)
+      .map_or(true, |objective| self.get(index) >= objective.required())
+  }
+
+  pub fn is_complete(&self, definition: &QuestDefinition) -> bool {
+    (0..definition.objectives.len()).all(|index| self.is_done(definition, index))
+  }
+
+  /// The unfinished objectives that can be worked on: all of them, or only the first one of
+  /// ordered quests.
+  pub fn open_objectives<'a>(
+    &'a self,
+    definition: &'a QuestDefinition,
+  ) -> impl Iterator<Item = (usize, &'a Objective)> {
+    definition
+      .objectives
+      .iter()
+      .enumerate()
+      .filter(|(index, _)| !self.is_done(definition, *index))
+      .take(if definition.ordered { 1 } else { usize::MAX })
+  }
+}
+
+/// The quests the player is on and has completed, saved along with the game.
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct QuestLog {
+  pub active: Vec<QuestProgress>,
+  pub completed: HashSet<String>,
+}
+
+impl QuestLog {
+  pub fn is_active(&self, quest: &str) -> bool {
+    self.active.iter().any(|progress| progress.quest == quest)
+  }
+
+  /// Puts the quest in the log unless the player is on it or has done it already. Returns whether
+  /// it was started.
+  pub fn start(&mut self, quest: &str, definition: &QuestDefinition) -> bool {
+    if self.is_active(quest) || self.completed.contains(quest) {
+      return false;
+    }
+
+    self.active.push(QuestProgress {
+      quest: quest.to_string(),
+      objectives: vec![0; definition.objectives.len()],
+    });
+    true
+  }
+
+  /// Sets the progress of every open objective to what `update` makes of it, given its current
+  /// progress. Returns whether any of them moved.
+  pub fn record(
+    &mut self,
+    definitions: &QuestDefinitions,
+    mut update: impl FnMut(&Objective, u32) -> u32,
+  ) -> bool {
+    let mut changed = false;
+
+    for progress in self.active.iter_mut() {
+      let Some(definition) = definitions.get(&progress.quest) else {
+        continue;
+      };
+
+      let updates: Vec<(usize, u32)> = progress
+        .open_objectives(definition)
+        .map(|(index, objective)| {
+          let current = progress.get(index);
+          (index, update(objective, current).min(objective.required()))
+        })
+        .collect();
+
+      for (index, value) in updates {
+        if let Some(objective) = progress.objectives.get_mut(index) {
+          if *objective != value {
+            *objective = value;
+            changed = true;
+          }
+        }
+      }
+    }
+
+    changed
+  }
+}
+
+/// A named region of a level for [`Objective::Reach`], as large as the LDtk entity.
+#[derive(Component, Clone, Debug, Default)]
+pub struct QuestArea {
+  pub name: String,
+  pub size: Vec2,
+}
+
+impl From<EntityInstance> for QuestArea {
+  fn from(entity_instance: EntityInstance) -> QuestArea {
+    QuestArea {
+      name: field_string(&entity_instance, "Name").unwrap_or_default(),
+      size: Vec2::new(entity_instance.width as f32, entity_instance.height as f32),
+    }
+  }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct QuestAreaBundle {
+  #[from_entity_instance]
+  pub quest_area: QuestArea,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
+
+#[derive(Clone, Debug)]
+pub struct StartQuest {
+  pub quest: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct QuestCompleted {
+  pub quest: String,
+}
@@ -0,0 +1,35 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::{
+  pause::systems::{freeze_gameplay, resume_gameplay},
+  utils::ron_asset::RonAssetAppExt,
+  GameState,
+};
+
+use super::{
+  screen::{despawn_quest_log_screen, spawn_quest_log_screen},
+  systems::reset_quest_log,
+  QuestCompleted, QuestDefinitions, QuestLog, StartQuest,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_ron_asset::<QuestDefinitions>(&["quests.ron"])
+      .init_resource::<QuestLog>()
+      .add_event::<StartQuest>()
+      .add_event::<QuestCompleted>()
+      .add_enter_system(GameState::MainMenu, reset_quest_log)
+      .add_exit_system(GameState::GameOver, reset_quest_log)
+      .add_exit_system(GameState::Victory, reset_quest_log)
+      .add_enter_system(GameState::QuestLog, spawn_quest_log_screen)
+      .add_enter_system(GameState::QuestLog, freeze_gameplay)
+      .add_exit_system(GameState::QuestLog, despawn_quest_log_screen)
+      .add_exit_system(GameState::QuestLog, resume_gameplay)
+      .add_system_set(super::systems::add_systems())
+      .add_system_set(super::screen::add_systems());
+  }
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  inventory::{ItemAssets, ItemDefinitions},
+  ui::{self, UiAssets},
+  GameState,
+};
+
+use super::{QuestAssets, QuestDefinitions, QuestLog};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new().label("quest-screen").with_system(
+    refresh_quest_log_screen
+      .run_in_state(GameState::QuestLog)
+      .label("quest-screen-refresh"),
+  )
+}
+
+#[derive(Component)]
+pub struct QuestLogScreen;
+
+#[derive(Component)]
+pub struct QuestLogList;
+
+pub fn spawn_quest_log_screen(mut commands: Commands, ui_assets: Res<UiAssets>) {
+  commands
+    .spawn((ui::overlay(Color::rgba(0., 0., 0., 0.5)), QuestLogScreen))
+    .with_children(|overlay| {
+      overlay.spawn(ui::panel()).with_children(|panel| {
+        panel.spawn(ui_assets.text("Quests", 32.));
+        panel.spawn((
+          NodeBundle {
+            style: Style {
+              flex_direction: FlexDirection::Column,
+              margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(8.), Val::Px(0.)),
+              ..default()
+            },
+            ..default()
+          },
+          QuestLogList,
+        ));
+      });
+    });
+}
+
+pub fn despawn_quest_log_screen(
+  mut commands: Commands,
+  screens: Query<Entity, With<QuestLogScreen>>,
+) {
+  for screen in screens.iter() {
+    commands.entity(screen).despawn_recursive();
+  }
+}
+
+/// Rebuilds the list whenever it's first shown or the quest log changes. Ordered quests only show
+/// their objectives up to the one being worked on.
+fn refresh_quest_log_screen(
+  mut commands: Commands,
+  lists: Query<Entity, Added<QuestLogList>>,
+  all_lists: Query<Entity, With<QuestLogList>>,
+  quest_log: Res<QuestLog>,
+  ui_assets: Res<UiAssets>,
+  quest_assets: Res<QuestAssets>,
+  quest_definitions: Res<Assets<QuestDefinitions>>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+) {
+  let Some(definitions) = quest_definitions.get(&quest_assets.definitions) else {
+    return;
+  };
+  let item_definitions = item_definitions.get(&item_assets.definitions);
+
+  let stale_lists: Vec<Entity> = if quest_log.is_changed() {
+    all_lists.iter().collect()
+  } else {
+    lists.iter().collect()
+  };
+
+  for list in stale_lists {
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|list| {
+      if quest_log.active.is_empty() {
+        list.spawn(ui_assets.text("No quests", 20.));
+      }
+
+      for progress in &quest_log.active {
+        let Some(definition) = definitions.get(&progress.quest) else {
+          continue;
+        };
+
+        list.spawn(ui_assets.text(&definition.name, 22.).with_style(Style {
+          margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(8.), Val::Px(0.)),
+          ..default()
+        }));
+        if !definition.description.is_empty() {
+          list.spawn(ui_assets.text(&definition.description, 16.));
+        }
+
+        let current = progress
+          .open_objectives(definition)
+          .next()
+          .map_or(definition.objectives.len(), |(index, _)| index);
+
+        for (index, objective) in definition.objectives.iter().enumerate() {
+          if definition.ordered && index > current {
+            break;
+          }
+
+          let mark = if progress.is_done(definition, index) {
+            "x"
+          } else {
+            " "
+          };
+          let mut label = format!("[{mark}] {}", objective.describe(item_definitions));
+          if objective.required() > 1 {
+            let count = progress.objectives.get(index).copied().unwrap_or_default();
+            label.push_str(&format!(" ({count}/{})", objective.required()));
+          }
+
+          list.spawn(ui_assets.text(label, 18.).with_style(Style {
+            margin: UiRect::new(Val::Px(12.), Val::Px(0.), Val::Px(0.), Val::Px(0.)),
+            ..default()
+          }));
+        }
+      }
+
+      let mut completed: Vec<&str> = quest_log
+        .completed
+        .iter()
+        .filter_map(|quest| definitions.get(quest))
+        .map(|definition| definition.name.as_str())
+        .collect();
+
+      if !completed.is_empty() {
+        completed.sort_unstable();
+        list.spawn(ui_assets.text("Completed", 22.).with_style(Style {
+          margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(12.), Val::Px(0.)),
+          ..default()
+        }));
+        for name in completed {
+          list.spawn(ui_assets.text(name, 18.));
+        }
+      }
+    });
+  }
+}
@@ -0,0 +1,258 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+  combat::DeathEvent,
+  companion::Companion,
+  dialogue::DialogueEnded,
+  enemy::{Enemy, EnemyArchetype},
+  faction::Reputation,
+  inventory::{Inventory, ItemAssets, ItemDefinitions},
+  npc::Npc,
+  player::{state_machine::TopDownAction, Player},
+  save::WorldProgress,
+  shop::{give_item, Wallet},
+  GameState,
+};
+
+use super::{
+  Objective, QuestArea, QuestAssets, QuestCompleted, QuestDefinitions, QuestLog, QuestProgress,
+  Reward, StartQuest,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("quest")
+    .with_system(start_quests.label("quest-start").after("dialogue-advance"))
+    .with_system(
+      track_objectives
+        .label("quest-track-objectives")
+        .after("quest-start")
+        .after("combat-apply-damage")
+        .after("inventory-collect"),
+    )
+    .with_system(
+      complete_quests
+        .label("quest-complete")
+        .after("quest-track-objectives"),
+    )
+    .with_system(
+      open_quest_log
+        .run_in_state(GameState::Playing)
+        .label("quest-open-log"),
+    )
+    .with_system(
+      close_quest_log
+        .run_in_state(GameState::QuestLog)
+        .label("quest-close-log"),
+    )
+}
+
+pub fn reset_quest_log(mut quest_log: ResMut<QuestLog>) {
+  *quest_log = QuestLog::default();
+}
+
+fn start_quests(
+  mut start_events: EventReader<StartQuest>,
+  mut quest_log: ResMut<QuestLog>,
+  quest_assets: Res<QuestAssets>,
+  quest_definitions: Res<Assets<QuestDefinitions>>,
+) {
+  let Some(definitions) = quest_definitions.get(&quest_assets.definitions) else {
+    return;
+  };
+
+  for StartQuest { quest } in start_events.iter() {
+    let Some(definition) = definitions.get(quest) else {
+      warn!("Unknown quest {quest:?}");
+      continue;
+    };
+
+    if quest_log.start(quest, definition) {
+      info!("Started quest {:?}", definition.name);
+    }
+  }
+}
+
+type PlayerGet<'a> = (&'a GlobalTransform, &'a Inventory);
+
+/// Moves the objectives of active quests along: enemies defeated by the player or their companion,
+/// conversations finished, areas reached and items carried.
+fn track_objectives(
+  mut quest_log: ResMut<QuestLog>,
+  quest_assets: Res<QuestAssets>,
+  quest_definitions: Res<Assets<QuestDefinitions>>,
+  mut death_events: EventReader<DeathEvent>,
+  mut ended_events: EventReader<DialogueEnded>,
+  archetypes: Query<&EnemyArchetype, With<Enemy>>,
+  credited: Query<(), Or<(With<Player>, With<Companion>)>>,
+  npcs: Query<&Npc>,
+  players: Query<PlayerGet, With<Player>>,
+  areas: Query<(&QuestArea, &GlobalTransform)>,
+) {
+  let Some(definitions) = quest_definitions.get(&quest_assets.definitions) else {
+    return;
+  };
+
+  let killed: Vec<&EnemyArchetype> = death_events
+    .iter()
+    .filter(|death_event| {
+      death_event
+        .killer
+        .map_or(false, |killer| credited.contains(killer))
+    })
+    .filter_map(|death_event| archetypes.get(death_event.entity).ok())
+    .collect();
+
+  let talked_to: Vec<&str> = ended_events
+    .iter()
+    .filter_map(|ended_event| npcs.get(ended_event.speaker).ok())
+    .map(|npc| npc.name.as_str())
+    .collect();
+
+  let player = players.get_single().ok();
+
+  let reached: Vec<&str> = player
+    .map(|(player_transform, _)| {
+      let position = player_transform.translation().truncate();
+      areas
+        .iter()
+        .filter(|(area, transform)| {
+          let offset = (position - transform.translation().truncate()).abs();
+          offset.x <= area.size.x / 2. && offset.y <= area.size.y / 2.
+        })
+        .map(|(area, _)| area.name.as_str())
+        .collect()
+    })
+    .unwrap_or_default();
+
+  // Most frames nothing happens, and the quest log screen only refreshes when it changes
+  let changed = quest_log
+    .bypass_change_detection()
+    .record(definitions, |objective, progress| match objective {
+      Objective::Kill { archetype, .. } => {
+        let kills = killed
+          .iter()
+          .filter(|EnemyArchetype(killed)| archetype.is_none() || killed == archetype)
+          .count();
+        progress + kills as u32
+      }
+      Objective::Reach(area) => progress.max(reached.contains(&area.as_str()) as u32),
+      Objective::TalkTo(npc) => progress.max(talked_to.contains(&npc.as_str()) as u32),
+      Objective::Collect { item, .. } => {
+        let carried = player.map_or(0, |(_, inventory)| inventory.count(item));
+        progress.max(carried)
+      }
+    });
+
+  if changed {
+    quest_log.set_changed();
+  }
+}
+
+/// Hands out the rewards of quests whose objectives are all done and moves them to the completed
+/// ones.
+fn complete_quests(
+  mut quest_log: ResMut<QuestLog>,
+  mut completed_events: EventWriter<QuestCompleted>,
+  mut world_progress: ResMut<WorldProgress>,
+  mut inventories: Query<&mut Inventory, With<Player>>,
+  mut wallets: Query<&mut Wallet, With<Player>>,
+  quest_assets: Res<QuestAssets>,
+  quest_definitions: Res<Assets<QuestDefinitions>>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+  mut reputation: ResMut<Reputation>,
+) {
+  let Some(definitions) = quest_definitions.get(&quest_assets.definitions) else {
+    return;
+  };
+
+  let is_complete = |quest: &str, progress: &QuestProgress| {
+    definitions
+      .get(quest)
+      .map_or(false, |definition| progress.is_complete(definition))
+  };
+
+  if !quest_log
+    .active
+    .iter()
+    .any(|progress| is_complete(&progress.quest, progress))
+  {
+    return;
+  }
+
+  let (completed, active) = quest_log
+    .active
+    .drain(..)
+    .partition::<Vec<_>, _>(|progress| is_complete(&progress.quest, progress));
+  quest_log.active = active;
+
+  for progress in completed {
+    let Some(definition) = definitions.get(&progress.quest) else {
+      continue;
+    };
+
+    for reward in &definition.rewards {
+      match reward {
+        Reward::Item { item, amount } => {
+          let (Ok(mut inventory), Some(item_definitions)) = (
+            inventories.get_single_mut(),
+            item_definitions.get(&item_assets.definitions),
+          ) else {
+            continue;
+          };
+
+          let mut wallet = wallets.get_single_mut().ok();
+          let leftover = give_item(
+            &mut inventory,
+            wallet.as_deref_mut(),
+            item_definitions,
+            item,
+            *amount,
+          );
+          if leftover > 0 {
+            warn!("Inventory full, {leftover} {item} were lost");
+          }
+        }
+        Reward::Flag(flag) => {
+          world_progress.flags.insert(flag.clone());
+        }
+        Reward::Reputation { faction, amount } => {
+          reputation.change(*faction, *amount);
+        }
+      }
+    }
+
+    info!("Completed quest {:?}", definition.name);
+    completed_events.send(QuestCompleted {
+      quest: progress.quest.clone(),
+    });
+    quest_log.completed.insert(progress.quest);
+  }
+}
+
+fn open_quest_log(
+  mut commands: Commands,
+  players: Query<&ActionState<TopDownAction>, With<Player>>,
+) {
+  if players
+    .iter()
+    .any(|action_state| action_state.just_pressed(TopDownAction::Quests))
+  {
+    commands.insert_resource(NextState(GameState::QuestLog));
+  }
+}
+
+fn close_quest_log(
+  mut commands: Commands,
+  players: Query<&ActionState<TopDownAction>, With<Player>>,
+) {
+  if players.iter().any(|action_state| {
+    action_state.just_pressed(TopDownAction::Quests)
+      || action_state.just_pressed(TopDownAction::Pause)
+  }) {
+    commands.insert_resource(NextState(GameState::Playing));
+  }
+}
@@ -0,0 +1,150 @@
+use std::{
+  collections::HashSet,
+  fmt, fs, io,
+  path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  clock::GameClock,
+  faction::Reputation,
+  inventory::Inventory,
+  quest::QuestLog,
+  shop::{ShopStocks, Wallet},
+};
+
+pub mod plugin;
+pub mod systems;
+
+/// Bumped whenever [`SaveData`] changes in a way older files can't be read as.
+pub const SAVE_VERSION: u32 = 1;
+pub const SAVE_DIRECTORY: &str = "saves";
+pub const SAVE_SLOTS: usize = 3;
+
+/// What has permanently changed in the LDtk world during a run, keyed by entity instance IID so it
+/// can be re-applied whenever a level (re)spawns.
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WorldProgress {
+  pub opened_chests: HashSet<String>,
+  pub collected_pickups: HashSet<String>,
+  pub defeated_enemies: HashSet<String>,
+  /// Story flags raised by dialogue and checked by its conditions.
+  #[serde(default)]
+  pub flags: HashSet<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum EnemyState {
+  Idle,
+  Follow,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerSave {
+  pub translation: [f32; 2],
+  pub health: f32,
+  pub inventory: Inventory,
+  #[serde(default)]
+  pub wallet: Wallet,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EnemySave {
+  pub iid: String,
+  /// The level the enemy has chased the player into, if it left the one it was authored in.
+  #[serde(default)]
+  pub level_iid: Option<String>,
+  pub translation: [f32; 2],
+  pub health: f32,
+  pub state: EnemyState,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SaveData {
+  pub version: u32,
+  pub level_iid: String,
+  pub player: PlayerSave,
+  pub enemies: Vec<EnemySave>,
+  pub world: WorldProgress,
+  #[serde(default)]
+  pub clock: GameClock,
+  #[serde(default)]
+  pub quests: QuestLog,
+  #[serde(default)]
+  pub shops: ShopStocks,
+  #[serde(default)]
+  pub reputation: Reputation,
+}
+
+/// A save waiting for its level to spawn before it can be applied to the player and enemies.
+#[derive(Resource, Clone, Debug)]
+pub struct PendingLoad(pub SaveData);
+
+/// Writes the current game into `slot`.
+#[derive(Clone, Copy, Debug)]
+pub struct SaveGame {
+  pub slot: usize,
+}
+
+/// Restores the game saved in `slot`.
+#[derive(Clone, Copy, Debug)]
+pub struct LoadGame {
+  pub slot: usize,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+  Io(io::Error),
+  Serialize(ron::Error),
+  Deserialize(ron::error::SpannedError),
+  UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SaveError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SaveError::Io(error) => write!(f, "{error}"),
+      SaveError::Serialize(error) => write!(f, "{error}"),
+      SaveError::Deserialize(error) => write!(f, "{error}"),
+      SaveError::UnsupportedVersion(version) => {
+        write!(
+          f,
+          "save version {version} is not supported (expected {SAVE_VERSION})"
+        )
+      }
+    }
+  }
+}
+
+impl std::error::Error for SaveError {}
+
+pub fn slot_path(slot: usize) -> PathBuf {
+  Path::new(SAVE_DIRECTORY).join(format!("slot_{slot}.ron"))
+}
+
+pub fn slot_exists(slot: usize) -> bool {
+  slot_path(slot).exists()
+}
+
+impl SaveData {
+  pub fn write(&self, slot: usize) -> Result<(), SaveError> {
+    let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+      .map_err(SaveError::Serialize)?;
+
+    fs::create_dir_all(SAVE_DIRECTORY).map_err(SaveError::Io)?;
+    fs::write(slot_path(slot), contents).map_err(SaveError::Io)
+  }
+
+  pub fn read(slot: usize) -> Result<Self, SaveError> {
+    let contents = fs::read_to_string(slot_path(slot)).map_err(SaveError::Io)?;
+    let save_data = ron::from_str::<SaveData>(&contents).map_err(SaveError::Deserialize)?;
+
+    if save_data.version != SAVE_VERSION {
+      return Err(SaveError::UnsupportedVersion(save_data.version));
+    }
+
+    Ok(save_data)
+  }
+}
@@ -0,0 +1,21 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::{systems::reset_world_progress, LoadGame, SaveGame, WorldProgress};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<WorldProgress>()
+      .add_event::<SaveGame>()
+      .add_event::<LoadGame>()
+      .add_enter_system(GameState::MainMenu, reset_world_progress)
+      .add_exit_system(GameState::GameOver, reset_world_progress)
+      .add_exit_system(GameState::Victory, reset_world_progress)
+      .add_system_set(super::systems::add_systems());
+  }
+}
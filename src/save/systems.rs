@@ -0,0 +1,329 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use iyes_loopless::prelude::*;
+use iyes_progress::prelude::*;
+
+use crate::{
+  clock::GameClock,
+  combat::{DeathEvent, Health, Target},
+  enemy::{
+    self,
+    state_machine::{Follow, Idle},
+    Enemy,
+  },
+  faction::Reputation,
+  interaction::Interactable,
+  inventory::Inventory,
+  loot::{Chest, ChestOpened, PickupCollected, CHEST_OPEN_FRAME},
+  map::DespawnWithWorld,
+  navigation::InLevel,
+  player::Player,
+  quest::QuestLog,
+  shop::{ShopStocks, Wallet},
+  utils::ldtk::selected_level,
+  GameState,
+};
+
+use super::{
+  EnemySave, EnemyState, LoadGame, PendingLoad, PlayerSave, SaveData, SaveGame, WorldProgress,
+  SAVE_VERSION,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("save")
+    .with_system(
+      track_world_progress
+        .label("save-track-world-progress")
+        .after("loot-collect-pickups")
+        .after("loot-open-chests")
+        .after("combat-apply-damage"),
+    )
+    .with_system(
+      apply_world_progress
+        .label("save-apply-world-progress")
+        // Its despawns must come after the components other spawn systems insert
+        .after("enemy-spawn"),
+    )
+    .with_system(save_game.label("save-save-game"))
+    .with_system(load_game.label("save-load-game"))
+    .with_system(
+      apply_pending_load
+        .run_in_state(GameState::LevelLoading)
+        .run_if_resource_exists::<PendingLoad>()
+        .label("save-apply-pending-load")
+        .after("enemy-spawn"),
+    )
+    .with_system(
+      pending_load_progress
+        .track_progress()
+        .run_in_state(GameState::LevelLoading)
+        .label("save-pending-load-progress"),
+    )
+}
+
+pub fn reset_world_progress(mut world_progress: ResMut<WorldProgress>) {
+  *world_progress = WorldProgress::default();
+}
+
+fn track_world_progress(
+  mut world_progress: ResMut<WorldProgress>,
+  mut opened_events: EventReader<ChestOpened>,
+  mut collected_events: EventReader<PickupCollected>,
+  mut death_events: EventReader<DeathEvent>,
+  entity_instances: Query<&EntityInstance>,
+  enemies: Query<(), With<Enemy>>,
+) {
+  let iid = |entity: Entity| {
+    entity_instances
+      .get(entity)
+      .ok()
+      .map(|entity_instance| entity_instance.iid.clone())
+  };
+
+  for event in opened_events.iter() {
+    world_progress.opened_chests.extend(iid(event.chest));
+  }
+
+  // Loot dropped at runtime has no IID, so it's simply not remembered
+  for event in collected_events.iter() {
+    world_progress.collected_pickups.extend(iid(event.pickup));
+  }
+
+  for event in death_events.iter() {
+    if enemies.contains(event.entity) {
+      world_progress.defeated_enemies.extend(iid(event.entity));
+    }
+  }
+}
+
+/// Brings freshly spawned LDtk entities in line with the [`WorldProgress`], so chests stay open and
+/// collected pickups and defeated enemies stay gone when their level spawns again.
+fn apply_world_progress(
+  mut commands: Commands,
+  world_progress: Res<WorldProgress>,
+  mut entities: Query<
+    (
+      Entity,
+      &EntityInstance,
+      Option<&mut Chest>,
+      Option<&mut TextureAtlasSprite>,
+    ),
+    Added<EntityInstance>,
+  >,
+) {
+  for (entity, entity_instance, chest, sprite) in entities.iter_mut() {
+    let iid = &entity_instance.iid;
+
+    if world_progress.collected_pickups.contains(iid)
+      || world_progress.defeated_enemies.contains(iid)
+    {
+      commands.entity(entity).despawn_recursive();
+      continue;
+    }
+
+    if let Some(mut chest) = chest {
+      if world_progress.opened_chests.contains(iid) {
+        chest.opened = true;
+        commands.entity(entity).remove::<Interactable>();
+        if let Some(mut sprite) = sprite {
+          sprite.index = CHEST_OPEN_FRAME;
+        }
+      }
+    }
+  }
+}
+
+fn save_game(
+  mut save_events: EventReader<SaveGame>,
+  world_progress: Res<WorldProgress>,
+  clock: Res<GameClock>,
+  quest_log: Res<QuestLog>,
+  shop_stocks: Res<ShopStocks>,
+  reputation: Res<Reputation>,
+  level_selection: Res<LevelSelection>,
+  level_query: Query<&Handle<LdtkLevel>>,
+  ldtk_levels: Res<Assets<LdtkLevel>>,
+  players: Query<(&Transform, &Health, &Inventory, &Wallet), With<Player>>,
+  enemies: Query<
+    (
+      &EntityInstance,
+      &Transform,
+      &Health,
+      Option<&InLevel>,
+      Option<&Follow>,
+    ),
+    With<Enemy>,
+  >,
+) {
+  for &SaveGame { slot } in save_events.iter() {
+    let Some(ldtk_level) = selected_level(&level_selection, level_query.iter(), &ldtk_levels)
+    else {
+      warn!("Can't save slot {slot}: no level is loaded");
+      continue;
+    };
+
+    let Ok((transform, health, inventory, wallet)) = players.get_single() else {
+      warn!("Can't save slot {slot}: there is no player");
+      continue;
+    };
+
+    let save_data = SaveData {
+      version: SAVE_VERSION,
+      level_iid: ldtk_level.level.iid.clone(),
+      player: PlayerSave {
+        translation: transform.translation.truncate().to_array(),
+        health: health.current,
+        inventory: inventory.clone(),
+        wallet: *wallet,
+      },
+      enemies: enemies
+        .iter()
+        .map(
+          |(entity_instance, transform, health, in_level, follow)| EnemySave {
+            iid: entity_instance.iid.clone(),
+            level_iid: in_level.map(|InLevel(level_iid)| level_iid.clone()),
+            translation: transform.translation.truncate().to_array(),
+            health: health.current,
+            state: if follow.is_some() {
+              EnemyState::Follow
+            } else {
+              EnemyState::Idle
+            },
+          },
+        )
+        .collect(),
+      world: world_progress.clone(),
+      clock: *clock,
+      quests: quest_log.clone(),
+      shops: shop_stocks.clone(),
+      reputation: reputation.clone(),
+    };
+
+    match save_data.write(slot) {
+      Ok(()) => info!("Saved game to slot {slot}"),
+      Err(error) => error!("Failed to save slot {slot}: {error}"),
+    }
+  }
+}
+
+/// Reads the save and restarts the world. The player and enemies are restored by
+/// [`apply_pending_load`] once they have spawned.
+fn load_game(
+  mut commands: Commands,
+  mut load_events: EventReader<LoadGame>,
+  worlds: Query<Entity, With<Handle<LdtkAsset>>>,
+  loose_entities: Query<Entity, With<DespawnWithWorld>>,
+) {
+  let Some(&LoadGame { slot }) = load_events.iter().last() else {
+    return;
+  };
+
+  let save_data = match SaveData::read(slot) {
+    Ok(save_data) => save_data,
+    Err(error) => {
+      error!("Failed to load slot {slot}: {error}");
+      return;
+    }
+  };
+
+  for entity in worlds.iter().chain(loose_entities.iter()) {
+    commands.entity(entity).despawn_recursive();
+  }
+
+  commands.insert_resource(save_data.world.clone());
+  commands.insert_resource(save_data.clock);
+  commands.insert_resource(save_data.quests.clone());
+  commands.insert_resource(save_data.shops.clone());
+  commands.insert_resource(save_data.reputation.clone());
+  // The player is only spawned with the level it was authored in, so the run starts there and
+  // moves on to the saved level once the player exists
+  commands.insert_resource(LevelSelection::Index(0));
+  commands.insert_resource(PendingLoad(save_data));
+  commands.insert_resource(NextState(GameState::LevelLoading));
+}
+
+/// Switches to the saved level once the player exists, then restores the player and enemies when
+/// that level has finished spawning.
+fn apply_pending_load(
+  mut commands: Commands,
+  pending_load: Res<PendingLoad>,
+  mut level_events: EventReader<LevelEvent>,
+  mut level_selection: ResMut<LevelSelection>,
+  level_query: Query<&Handle<LdtkLevel>>,
+  ldtk_levels: Res<Assets<LdtkLevel>>,
+  mut players: Query<
+    (
+      Entity,
+      &mut Transform,
+      &mut Health,
+      &mut Inventory,
+      &mut Wallet,
+    ),
+    With<Player>,
+  >,
+  mut enemies: Query<
+    (Entity, &EntityInstance, &mut Transform, &mut Health),
+    (With<Enemy>, Without<Player>),
+  >,
+) {
+  let Ok((player_entity, mut transform, mut health, mut inventory, mut wallet)) =
+    players.get_single_mut()
+  else {
+    return;
+  };
+
+  let PendingLoad(save_data) = &*pending_load;
+
+  let Some(ldtk_level) = selected_level(&level_selection, level_query.iter(), &ldtk_levels) else {
+    return;
+  };
+
+  if ldtk_level.level.iid != save_data.level_iid {
+    *level_selection = LevelSelection::Iid(save_data.level_iid.clone());
+    return;
+  }
+
+  let level_spawned = level_events.iter().any(|level_event| {
+    matches!(level_event, LevelEvent::Transformed(iid) if *iid == save_data.level_iid)
+  });
+
+  if !level_spawned {
+    return;
+  }
+
+  transform.translation = Vec2::from(save_data.player.translation).extend(transform.translation.z);
+  health.current = save_data.player.health.min(health.max);
+  *inventory = save_data.player.inventory.clone();
+  *wallet = save_data.player.wallet;
+
+  for (enemy_entity, entity_instance, mut transform, mut health) in enemies.iter_mut() {
+    let Some(enemy_save) = save_data
+      .enemies
+      .iter()
+      .find(|enemy_save| enemy_save.iid == entity_instance.iid)
+    else {
+      continue;
+    };
+
+    transform.translation = Vec2::from(enemy_save.translation).extend(transform.translation.z);
+    health.current = enemy_save.health.min(health.max);
+
+    let following = enemy_save.state == EnemyState::Follow;
+    let mut enemy_commands = commands.entity(enemy_entity);
+    enemy_commands.insert(enemy::systems::state_machine(following));
+    if let Some(level_iid) = &enemy_save.level_iid {
+      enemy_commands.insert(InLevel(level_iid.clone()));
+    }
+    if following {
+      enemy_commands.insert(Target(Some(player_entity)));
+      enemy_commands.remove::<Idle>();
+    }
+  }
+
+  commands.remove_resource::<PendingLoad>();
+}
+
+fn pending_load_progress(pending_load: Option<Res<PendingLoad>>) -> Progress {
+  pending_load.is_none().into()
+}
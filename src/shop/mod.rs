@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_asset_loader::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  inventory::{Inventory, ItemDefinitions},
+  utils::ldtk::field_string,
+};
+
+pub mod plugin;
+pub mod screen;
+pub mod systems;
+
+/// The item that is money: it goes into the [`Wallet`] rather than taking up inventory slots.
+pub const CURRENCY_ITEM: &str = "coin";
+
+/// How many coins its owner carries.
+#[derive(Component, Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct Wallet {
+  pub coins: u32,
+}
+
+/// Gives `amount` of `item` to an owner, coins straight into its `wallet` so they never need a free
+/// slot. Returns the amount that did not fit.
+pub fn give_item(
+  inventory: &mut Inventory,
+  wallet: Option<&mut Wallet>,
+  definitions: &ItemDefinitions,
+  item: &str,
+  amount: u32,
+) -> u32 {
+  match wallet {
+    Some(wallet) if item == CURRENCY_ITEM => {
+      wallet.coins += amount;
+      0
+    }
+    _ => inventory.add(definitions, item, amount),
+  }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct StockEntry {
+  pub item: String,
+  /// How many the shop holds after restocking.
+  pub amount: u32,
+  /// Overrides the price worked out from the item's value.
+  #[serde(default)]
+  pub price: Option<u32>,
+}
+
+fn default_markup() -> f32 {
+  1.
+}
+
+fn default_sell_rate() -> f32 {
+  0.5
+}
+
+fn default_restock_hours() -> f32 {
+  24.
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ShopDefinition {
+  pub name: String,
+  pub stock: Vec<StockEntry>,
+  /// Times the item's value the shop sells for.
+  #[serde(default = "default_markup")]
+  pub markup: f32,
+  /// Times the item's value the shop buys for.
+  #[serde(default = "default_sell_rate")]
+  pub sell_rate: f32,
+  /// The items the shop buys from the player, or any item with a value.
+  #[serde(default)]
+  pub buys: Option<Vec<String>>,
+  /// In-game hours between restocks.
+  #[serde(default = "default_restock_hours")]
+  pub restock_hours: f32,
+}
+
+impl ShopDefinition {
+  pub fn buy_price(&self, items: &ItemDefinitions, item: &str) -> u32 {
+    self
+      .stock
+      .iter()
+      .find(|entry| entry.item == item)
+      .and_then(|entry| entry.price)
+      .unwrap_or_else(|| (items.value(item) as f32 * self.markup).ceil() as u32)
+      .max(1)
+  }
+
+  /// What the shop pays for one `item`, or `None` when it doesn't take it.
+  pub fn sell_price(&self, items: &ItemDefinitions, item: &str) -> Option<u32> {
+    if item == CURRENCY_ITEM {
+      return None;
+    }
+
+    if let Some(buys) = &self.buys {
+      if !buys.iter().any(|bought| bought == item) {
+        return None;
+      }
+    }
+
+    let price = (items.value(item) as f32 * self.sell_rate).floor() as u32;
+    (price > 0).then_some(price)
+  }
+}
+
+/// Every shop of the game keyed by its id, loaded from `assets/shops/game.shops.ron`.
+#[derive(Clone, Debug, Default, Deserialize, TypeUuid)]
+#[serde(transparent)]
+#[uuid = "e7b2d4a9-1c6f-4a38-b05e-8f3c9d2a6b41"]
+pub struct ShopDefinitions(pub HashMap<String, ShopDefinition>);
+
+impl ShopDefinitions {
+  pub fn get(&self, shop: &str) -> Option<&ShopDefinition> {
+    self.0.get(shop)
+  }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct ShopAssets {
+  #[asset(path = "shops/game.shops.ron")]
+  pub definitions: Handle<ShopDefinitions>,
+}
+
+/// What a shop has left to sell. Kept apart from the shopkeeper, which despawns with its level.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ShopStock {
+  pub items: HashMap<String, u32>,
+  /// [`GameClock::total_minutes`](crate::clock::GameClock::total_minutes) of the last restock.
+  pub restocked_at: f32,
+}
+
+impl ShopStock {
+  pub fn amount(&self, item: &str) -> u32 {
+    self.items.get(item).copied().unwrap_or_default()
+  }
+}
+
+/// The stock of every shop, keyed by shop id.
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ShopStocks(pub HashMap<String, ShopStock>);
+
+/// An NPC the player trades with instead of talking to, running the shop from its `Shop` field.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Shopkeeper {
+  pub shop: Option<String>,
+}
+
+impl From<EntityInstance> for Shopkeeper {
+  fn from(entity_instance: EntityInstance) -> Shopkeeper {
+    Shopkeeper {
+      shop: field_string(&entity_instance, "Shop"),
+    }
+  }
+}
+
+/// The shop being shown, while in `GameState::Shop`.
+#[derive(Resource, Clone, Debug)]
+pub struct ActiveShop {
+  pub keeper: Entity,
+  pub shop: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeAction {
+  Buy,
+  Sell,
+}
+
+/// Buys one `item` from or sells one to the [`ActiveShop`].
+#[derive(Clone, Debug)]
+pub struct TradeEvent {
+  pub trader: Entity,
+  pub item: String,
+  pub action: TradeAction,
+}
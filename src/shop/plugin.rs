@@ -0,0 +1,34 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::{
+  pause::systems::{freeze_gameplay, resume_gameplay},
+  utils::ron_asset::RonAssetAppExt,
+  GameState,
+};
+
+use super::{
+  screen::{despawn_shop_screen, spawn_shop_screen},
+  systems::reset_shop_stocks,
+  ShopDefinitions, ShopStocks, TradeEvent,
+};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .add_ron_asset::<ShopDefinitions>(&["shops.ron"])
+      .init_resource::<ShopStocks>()
+      .add_event::<TradeEvent>()
+      .add_enter_system(GameState::MainMenu, reset_shop_stocks)
+      .add_exit_system(GameState::GameOver, reset_shop_stocks)
+      .add_exit_system(GameState::Victory, reset_shop_stocks)
+      .add_enter_system(GameState::Shop, spawn_shop_screen)
+      .add_enter_system(GameState::Shop, freeze_gameplay)
+      .add_exit_system(GameState::Shop, despawn_shop_screen)
+      .add_exit_system(GameState::Shop, resume_gameplay)
+      .add_system_set(super::systems::add_systems())
+      .add_system_set(super::screen::add_systems());
+  }
+}
@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  inventory::{Inventory, ItemAssets, ItemDefinitions},
+  player::Player,
+  ui::{self, UiAssets},
+  GameState,
+};
+
+use super::{ActiveShop, ShopAssets, ShopDefinitions, ShopStocks, TradeAction, TradeEvent, Wallet};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("shop-screen")
+    .with_system(
+      refresh_shop_screen
+        .run_in_state(GameState::Shop)
+        .label("shop-screen-refresh"),
+    )
+    .with_system(
+      press_shop_buttons
+        .run_in_state(GameState::Shop)
+        .label("shop-screen-buttons"),
+    )
+}
+
+#[derive(Component)]
+pub struct ShopScreen;
+
+/// Holds the title, the player's coins and both lists, rebuilt on every trade.
+#[derive(Component)]
+pub struct ShopContent;
+
+#[derive(Component, Clone)]
+pub struct ShopButton {
+  pub item: String,
+  pub action: TradeAction,
+}
+
+pub fn spawn_shop_screen(mut commands: Commands) {
+  commands
+    .spawn((ui::overlay(Color::rgba(0., 0., 0., 0.5)), ShopScreen))
+    .with_children(|overlay| {
+      overlay.spawn((ui::panel(), ShopContent));
+    });
+}
+
+pub fn despawn_shop_screen(mut commands: Commands, screens: Query<Entity, With<ShopScreen>>) {
+  for screen in screens.iter() {
+    commands.entity(screen).despawn_recursive();
+  }
+}
+
+type TraderGet<'a> = (
+  &'a Inventory,
+  &'a Wallet,
+  ChangeTrackers<Inventory>,
+  ChangeTrackers<Wallet>,
+);
+
+/// Lists what the shop sells and what the player can sell to it, whenever it's first shown or
+/// either side changes.
+fn refresh_shop_screen(
+  mut commands: Commands,
+  contents: Query<Entity, Added<ShopContent>>,
+  all_contents: Query<Entity, With<ShopContent>>,
+  active_shop: Option<Res<ActiveShop>>,
+  shop_stocks: Res<ShopStocks>,
+  traders: Query<TraderGet, With<Player>>,
+  ui_assets: Res<UiAssets>,
+  shop_assets: Res<ShopAssets>,
+  shop_definitions: Res<Assets<ShopDefinitions>>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+) {
+  let Some(active_shop) = active_shop else {
+    return;
+  };
+
+  let Ok((inventory, wallet, inventory_tracker, wallet_tracker)) = traders.get_single() else {
+    return;
+  };
+
+  let (Some(definition), Some(items), Some(stock)) = (
+    shop_definitions
+      .get(&shop_assets.definitions)
+      .and_then(|definitions| definitions.get(&active_shop.shop)),
+    item_definitions.get(&item_assets.definitions),
+    shop_stocks.0.get(&active_shop.shop),
+  ) else {
+    return;
+  };
+
+  let stale_contents: Vec<Entity> =
+    if shop_stocks.is_changed() || inventory_tracker.is_changed() || wallet_tracker.is_changed() {
+      all_contents.iter().collect()
+    } else {
+      contents.iter().collect()
+    };
+
+  // The shop's own stock first, then whatever players sold it
+  let mut for_sale: Vec<(&str, u32)> = definition
+    .stock
+    .iter()
+    .map(|entry| (entry.item.as_str(), stock.amount(&entry.item)))
+    .collect();
+  let mut sold_to_shop: Vec<(&str, u32)> = stock
+    .items
+    .iter()
+    .filter(|(item, _)| !definition.stock.iter().any(|entry| entry.item == **item))
+    .map(|(item, amount)| (item.as_str(), *amount))
+    .collect();
+  sold_to_shop.sort_unstable();
+  for_sale.extend(sold_to_shop);
+  for_sale.retain(|(_, amount)| *amount > 0);
+
+  let mut sellable: Vec<(&str, u32, u32)> = Vec::new();
+  for stack in &inventory.slots {
+    let Some(price) = definition.sell_price(items, &stack.item) else {
+      continue;
+    };
+
+    match sellable.iter_mut().find(|(item, ..)| *item == stack.item) {
+      Some((_, amount, _)) => *amount += stack.amount,
+      None => sellable.push((stack.item.as_str(), stack.amount, price)),
+    }
+  }
+
+  for content in stale_contents {
+    commands.entity(content).despawn_descendants();
+    commands.entity(content).with_children(|content| {
+      content.spawn(ui_assets.text(definition.name.clone(), 32.));
+      content.spawn(ui_assets.text(format!("Coins: {}", wallet.coins), 20.));
+
+      content.spawn(ui_assets.text("For sale", 22.).with_style(heading_style()));
+      if for_sale.is_empty() {
+        content.spawn(ui_assets.text("Sold out", 18.));
+      }
+      for (item, amount) in for_sale.iter() {
+        let price = definition.buy_price(items, item);
+        let label = format!("{} x{amount} - {price} coins", items.name(item));
+        spawn_row(content, &ui_assets, label, item, TradeAction::Buy);
+      }
+
+      content.spawn(ui_assets.text("Sell", 22.).with_style(heading_style()));
+      if sellable.is_empty() {
+        content.spawn(ui_assets.text("Nothing the shop wants", 18.));
+      }
+      for (item, amount, price) in sellable.iter() {
+        let label = format!("{} x{amount} - {price} coins", items.name(item));
+        spawn_row(content, &ui_assets, label, item, TradeAction::Sell);
+      }
+    });
+  }
+}
+
+fn heading_style() -> Style {
+  Style {
+    margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(12.), Val::Px(4.)),
+    ..default()
+  }
+}
+
+fn spawn_row(
+  content: &mut ChildBuilder,
+  ui_assets: &UiAssets,
+  label: String,
+  item: &str,
+  action: TradeAction,
+) {
+  content
+    .spawn(NodeBundle {
+      style: Style {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        ..default()
+      },
+      ..default()
+    })
+    .with_children(|row| {
+      row.spawn(ui_assets.text(label, 18.).with_style(Style {
+        flex_grow: 1.,
+        ..default()
+      }));
+
+      let text = match action {
+        TradeAction::Buy => "Buy",
+        TradeAction::Sell => "Sell",
+      };
+      row
+        .spawn((
+          ui::button(),
+          ShopButton {
+            item: item.to_string(),
+            action,
+          },
+        ))
+        .with_children(|button| {
+          button.spawn(ui_assets.text(text, 16.));
+        });
+    });
+}
+
+fn press_shop_buttons(
+  buttons: Query<(&Interaction, &ShopButton), Changed<Interaction>>,
+  players: Query<Entity, With<Player>>,
+  mut trade_events: EventWriter<TradeEvent>,
+) {
+  let Ok(trader) = players.get_single() else {
+    return;
+  };
+
+  for (interaction, button) in buttons.iter() {
+    if *interaction == Interaction::Clicked {
+      trade_events.send(TradeEvent {
+        trader,
+        item: button.item.clone(),
+        action: button.action,
+      });
+    }
+  }
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+  clock::GameClock,
+  interaction::InteractEvent,
+  inventory::{Inventory, ItemAssets, ItemDefinitions},
+  player::{state_machine::TopDownAction, Player},
+  GameState,
+};
+
+use super::{
+  ActiveShop, ShopAssets, ShopDefinitions, ShopStock, ShopStocks, Shopkeeper, TradeAction,
+  TradeEvent, Wallet,
+};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("shop")
+    .with_system(restock_shops.label("shop-restock").after("clock-advance"))
+    .with_system(
+      open_shops
+        .run_in_state(GameState::Playing)
+        .label("shop-open")
+        .after("interaction-interact")
+        .after("shop-restock"),
+    )
+    .with_system(close_shop.run_in_state(GameState::Shop).label("shop-close"))
+    .with_system(
+      apply_trades
+        .run_in_state(GameState::Shop)
+        .label("shop-apply-trades")
+        .after("shop-screen-buttons"),
+    )
+}
+
+pub fn reset_shop_stocks(mut shop_stocks: ResMut<ShopStocks>) {
+  *shop_stocks = ShopStocks::default();
+}
+
+/// Fills every shop back up to its stock once its restock time has passed, and the first time it's
+/// needed.
+fn restock_shops(
+  mut shop_stocks: ResMut<ShopStocks>,
+  shop_assets: Res<ShopAssets>,
+  shop_definitions: Res<Assets<ShopDefinitions>>,
+  clock: Res<GameClock>,
+) {
+  let Some(definitions) = shop_definitions.get(&shop_assets.definitions) else {
+    return;
+  };
+
+  let now = clock.total_minutes();
+
+  for (shop, definition) in definitions.0.iter() {
+    let due = shop_stocks.0.get(shop).map_or(true, |stock| {
+      now < stock.restocked_at || now - stock.restocked_at >= definition.restock_hours * 60.
+    });
+    if !due {
+      continue;
+    }
+
+    shop_stocks.0.insert(
+      shop.clone(),
+      ShopStock {
+        items: definition
+          .stock
+          .iter()
+          .map(|entry| (entry.item.clone(), entry.amount))
+          .collect(),
+        restocked_at: now,
+      },
+    );
+  }
+}
+
+/// Opens the shop of shopkeepers the player interacts with.
+fn open_shops(
+  mut commands: Commands,
+  mut interact_events: EventReader<InteractEvent>,
+  shopkeepers: Query<&Shopkeeper>,
+  shop_assets: Res<ShopAssets>,
+  shop_definitions: Res<Assets<ShopDefinitions>>,
+) {
+  let Some(definitions) = shop_definitions.get(&shop_assets.definitions) else {
+    return;
+  };
+
+  for &InteractEvent { target, .. } in interact_events.iter() {
+    let Some(shop) = shopkeepers
+      .get(target)
+      .ok()
+      .and_then(|shopkeeper| shopkeeper.shop.clone())
+    else {
+      continue;
+    };
+
+    if definitions.get(&shop).is_none() {
+      warn!("Unknown shop {shop:?}");
+      continue;
+    }
+
+    commands.insert_resource(ActiveShop {
+      keeper: target,
+      shop,
+    });
+    commands.insert_resource(NextState(GameState::Shop));
+  }
+}
+
+fn close_shop(mut commands: Commands, players: Query<&ActionState<TopDownAction>, With<Player>>) {
+  if players.iter().any(|action_state| {
+    action_state.just_pressed(TopDownAction::Menus)
+      || action_state.just_pressed(TopDownAction::Pause)
+  }) {
+    commands.remove_resource::<ActiveShop>();
+    commands.insert_resource(NextState(GameState::Playing));
+  }
+}
+
+/// Exchanges items for coins with the [`ActiveShop`], one at a time. What the player sells goes
+/// into the shop's stock until it restocks.
+fn apply_trades(
+  mut trade_events: EventReader<TradeEvent>,
+  active_shop: Option<Res<ActiveShop>>,
+  mut shop_stocks: ResMut<ShopStocks>,
+  mut traders: Query<(&mut Inventory, &mut Wallet)>,
+  shop_assets: Res<ShopAssets>,
+  shop_definitions: Res<Assets<ShopDefinitions>>,
+  item_assets: Res<ItemAssets>,
+  item_definitions: Res<Assets<ItemDefinitions>>,
+) {
+  // Borrowing the stock mutably would otherwise refresh the screen every frame
+  if trade_events.is_empty() {
+    return;
+  }
+
+  let Some(active_shop) = active_shop else {
+    return;
+  };
+
+  let (Some(definition), Some(items)) = (
+    shop_definitions
+      .get(&shop_assets.definitions)
+      .and_then(|definitions| definitions.get(&active_shop.shop)),
+    item_definitions.get(&item_assets.definitions),
+  ) else {
+    return;
+  };
+
+  let Some(stock) = shop_stocks.0.get_mut(&active_shop.shop) else {
+    return;
+  };
+
+  for TradeEvent {
+    trader,
+    item,
+    action,
+  } in trade_events.iter()
+  {
+    let Ok((mut inventory, mut wallet)) = traders.get_mut(*trader) else {
+      continue;
+    };
+
+    match action {
+      TradeAction::Buy => {
+        let price = definition.buy_price(items, item);
+        if stock.amount(item) == 0 || wallet.coins < price {
+          continue;
+        }
+
+        if inventory.add(items, item, 1) > 0 {
+          warn!("Inventory full, can't buy {item}");
+          continue;
+        }
+
+        wallet.coins -= price;
+        if let Some(amount) = stock.items.get_mut(item) {
+          *amount -= 1;
+        }
+      }
+      TradeAction::Sell => {
+        let Some(price) = definition.sell_price(items, item) else {
+          continue;
+        };
+
+        if inventory.remove(item, 1) == 0 {
+          continue;
+        }
+
+        wallet.coins += price;
+        *stock.items.entry(item.clone()).or_default() += 1;
+      }
+    }
+  }
+}
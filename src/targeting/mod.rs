@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+pub mod plugin;
+pub mod systems;
+
+/// How far away combatants notice those they could fight, in pixels.
+pub const PERCEPTION_RANGE: f32 = 300.;
+
+/// Points of [`Threat`] each attacker loses per second, so old grudges are eventually dropped.
+pub const THREAT_DECAY: f32 = 0.5;
+
+/// How much each consideration weighs when a combatant picks its
+/// [`Target`](crate::combat::Target) among the candidates it perceives. The highest score wins.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TargetScoring {
+  /// Score lost from right next to the combatant to the edge of its [`PERCEPTION_RANGE`].
+  pub distance: f32,
+  /// Score per point of [`Threat`], so whoever hurts a combatant the most gets fought back.
+  pub threat: f32,
+  /// Score of candidates whose faction is hostile, over those only fought because they attacked.
+  pub hostile_faction: f32,
+  /// Kept by the current target, so combatants don't keep switching between close candidates.
+  pub current_target: f32,
+}
+
+impl Default for TargetScoring {
+  fn default() -> Self {
+    Self {
+      distance: 1.,
+      threat: 0.5,
+      hostile_faction: 1.,
+      current_target: 0.25,
+    }
+  }
+}
+
+impl TargetScoring {
+  pub fn score(&self, distance: f32, threat: f32, hostile: bool, current: bool) -> f32 {
+    let mut score = self.threat * threat - self.distance * distance / PERCEPTION_RANGE;
+    if hostile {
+      score += self.hostile_faction;
+    }
+    if current {
+      score += self.current_target;
+    }
+    score
+  }
+}
+
+/// The damage each attacker recently dealt to this combatant. Any threat makes an attacker a
+/// candidate target, even from a faction that isn't hostile.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Threat(pub HashMap<Entity, f32>);
+
+impl Threat {
+  pub fn of(&self, entity: Entity) -> f32 {
+    self.0.get(&entity).copied().unwrap_or_default()
+  }
+}
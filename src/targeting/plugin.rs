@@ -0,0 +1,13 @@
+use bevy::prelude::{App, Plugin};
+
+use super::TargetScoring;
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<TargetScoring>()
+      .add_system_set(super::systems::add_systems());
+  }
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+  combat::{DamageEvent, Dead, Health, Target},
+  faction::{Faction, FactionRelations, Reputation},
+  navigation::{Offscreen, WalkabilityGrid},
+  GameState,
+};
+
+use super::{TargetScoring, Threat, PERCEPTION_RANGE, THREAT_DECAY};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("targeting")
+    .with_system(
+      update_threat
+        .run_in_state(GameState::Playing)
+        .label("targeting-update-threat")
+        .after("combat-apply-damage"),
+    )
+    .with_system(
+      select_targets
+        .run_in_state(GameState::Playing)
+        .label("targeting-select-targets")
+        .after("targeting-update-threat")
+        .before("enemy-follow")
+        .before("combat-melee-attack"),
+    )
+}
+
+/// Fades every combatant's [`Threat`] and adds the damage dealt to it this frame.
+fn update_threat(
+  mut damage_events: EventReader<DamageEvent>,
+  mut threatened: Query<&mut Threat>,
+  time: Res<Time>,
+) {
+  let decay = THREAT_DECAY * time.delta_seconds();
+  for mut threat in threatened.iter_mut() {
+    if threat.0.is_empty() {
+      continue;
+    }
+
+    threat.0.retain(|_, amount| {
+      *amount -= decay;
+      *amount > 0.
+    });
+  }
+
+  for damage_event in damage_events.iter() {
+    let (Some(source), Ok(mut threat)) =
+      (damage_event.source, threatened.get_mut(damage_event.target))
+    else {
+      continue;
+    };
+
+    *threat.0.entry(source).or_default() += damage_event.amount;
+  }
+}
+
+type CombatantGet<'a> = (
+  Entity,
+  &'a Faction,
+  &'a mut Target,
+  Option<&'a Threat>,
+  &'a GlobalTransform,
+  Option<&'a Offscreen>,
+);
+
+type CandidateWhen = (With<Health>, Without<Dead>, Without<Offscreen>);
+
+/// Points every combatant at the best candidate it sees within [`PERCEPTION_RANGE`], scored
+/// by [`TargetScoring`], or at nobody. Candidates are those of a hostile faction and those that
+/// attacked it. Everything is weighed again every frame, so whoever spawns later, like a player
+/// after a respawn, is picked up as well. Off-screen combatants keep their target as long as it's
+/// alive, so chases across levels go on.
+fn select_targets(
+  mut combatants: Query<CombatantGet, (With<Health>, Without<Dead>)>,
+  candidates: Query<(Entity, &Faction, &GlobalTransform), CandidateWhen>,
+  alive: Query<(), (With<Health>, Without<Dead>)>,
+  relations: Res<FactionRelations>,
+  reputation: Res<Reputation>,
+  scoring: Res<TargetScoring>,
+  grid: Res<WalkabilityGrid>,
+) {
+  for (entity, faction, mut target, threat, transform, offscreen) in combatants.iter_mut() {
+    let best = if offscreen.is_some() {
+      target.0.filter(|target| alive.contains(*target))
+    } else {
+      let position = transform.translation().truncate();
+
+      candidates
+        .iter()
+        .filter(|(candidate, ..)| *candidate != entity)
+        .filter_map(|(candidate, candidate_faction, candidate_transform)| {
+          let distance = candidate_transform
+            .translation()
+            .truncate()
+            .distance(position);
+          let threat = threat.map_or(0., |threat| threat.of(candidate));
+          let hostile = relations.is_hostile(&reputation, *faction, *candidate_faction);
+          if distance > PERCEPTION_RANGE || !hostile && threat <= 0. {
+            return None;
+          }
+
+          // Walls hide candidates, water doesn't
+          let candidate_position = candidate_transform.translation().truncate();
+          if !grid.has_line_of_sight(position, candidate_position) {
+            return None;
+          }
+
+          let current = target.0 == Some(candidate);
+          Some((candidate, scoring.score(distance, threat, hostile, current)))
+        })
+        .max_by(|(_, score_a), (_, score_b)| score_a.total_cmp(score_b))
+        .map(|(candidate, _)| candidate)
+    };
+
+    if target.0 != best {
+      target.0 = best;
+    }
+  }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{interaction::Interactable, utils::ldtk::*};
+
+pub mod plugin;
+pub mod systems;
+
+/// How long each half of the fade between levels lasts, in seconds.
+pub const FADE_DURATION: f32 = 0.35;
+
+/// Sends the player to the [`EntryPoint`] named `target_entry` of the level identified by
+/// `target_level` (its LDtk identifier, e.g. `Level_1`) when interacted with.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Door {
+  pub target_level: String,
+  pub target_entry: String,
+}
+
+impl From<EntityInstance> for Door {
+  fn from(entity_instance: EntityInstance) -> Door {
+    Door {
+      target_level: field_string(&entity_instance, "TargetLevel").unwrap_or_default(),
+      target_entry: field_string(&entity_instance, "TargetEntry").unwrap_or_default(),
+    }
+  }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct DoorBundle {
+  #[from_entity_instance]
+  pub door: Door,
+  #[from_entity_instance]
+  pub interactable: Interactable,
+
+  #[sprite_sheet_bundle]
+  #[bundle]
+  sprite_bundle: SpriteSheetBundle,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
+
+/// A named spot where the player appears after going through a [`Door`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct EntryPoint {
+  pub name: String,
+}
+
+impl From<EntityInstance> for EntryPoint {
+  fn from(entity_instance: EntityInstance) -> EntryPoint {
+    EntryPoint {
+      name: field_string(&entity_instance, "Name").unwrap_or_default(),
+    }
+  }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct EntryPointBundle {
+  #[from_entity_instance]
+  pub entry_point: EntryPoint,
+
+  #[from_entity_instance]
+  entity_instance: EntityInstance,
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub enum LevelTransition {
+  #[default]
+  None,
+  FadingOut {
+    timer: Timer,
+    target_level: String,
+    target_entry: String,
+  },
+  /// The screen is black while the target level spawns.
+  Switching {
+    target_level: String,
+    target_entry: String,
+  },
+  FadingIn {
+    timer: Timer,
+  },
+}
+
+impl LevelTransition {
+  pub fn is_active(&self) -> bool {
+    !matches!(self, LevelTransition::None)
+  }
+}
+
+/// Full screen node faded in and out during a [`LevelTransition`].
+#[derive(Component)]
+pub struct FadeOverlay;
@@ -0,0 +1,17 @@
+use bevy::prelude::{App, Plugin};
+use iyes_loopless::prelude::AppLooplessStateExt;
+
+use crate::GameState;
+
+use super::{systems::reset_transition, LevelTransition};
+
+pub struct All;
+
+impl Plugin for All {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<LevelTransition>()
+      .add_enter_system(GameState::LevelLoading, reset_transition)
+      .add_system_set(super::systems::add_systems());
+  }
+}
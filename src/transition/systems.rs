@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use iyes_loopless::prelude::IntoConditionalSystem;
+
+use crate::{interaction::InteractEvent, player::Player, utils::ldtk::selected_level, GameState};
+
+use super::{Door, EntryPoint, FadeOverlay, LevelTransition, FADE_DURATION};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("transition")
+    .with_system(
+      use_doors
+        .run_in_state(GameState::Playing)
+        .label("transition-use-doors")
+        .after("interaction-interact"),
+    )
+    .with_system(
+      advance_transition
+        .run_in_state(GameState::Playing)
+        .label("transition-advance")
+        .after("transition-use-doors"),
+    )
+    .with_system(
+      place_player_at_entry
+        .run_in_state(GameState::Playing)
+        .label("transition-place-player")
+        .after("transition-advance"),
+    )
+    .with_system(update_fade_overlay.label("transition-fade-overlay"))
+}
+
+fn use_doors(
+  mut commands: Commands,
+  mut interact_events: EventReader<InteractEvent>,
+  mut transition: ResMut<LevelTransition>,
+  doors: Query<&Door>,
+) {
+  for &InteractEvent { target, .. } in interact_events.iter() {
+    let Ok(door) = doors.get(target) else {
+      continue;
+    };
+
+    if transition.is_active() {
+      continue;
+    }
+
+    if door.target_level.is_empty() {
+      warn!(
+        "Door leading to {:?} has no target level",
+        door.target_entry
+      );
+      continue;
+    }
+
+    *transition = LevelTransition::FadingOut {
+      timer: Timer::from_seconds(FADE_DURATION, TimerMode::Once),
+      target_level: door.target_level.clone(),
+      target_entry: door.target_entry.clone(),
+    };
+
+    commands.spawn((
+      NodeBundle {
+        style: Style {
+          size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+          position_type: PositionType::Absolute,
+          ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.).into(),
+        z_index: ZIndex::Global(i32::MAX),
+        ..default()
+      },
+      FadeOverlay,
+    ));
+  }
+}
+
+/// Switches the [`LevelSelection`] once the screen has faded out, and removes the overlay once it
+/// has faded back in.
+fn advance_transition(
+  mut commands: Commands,
+  mut transition: ResMut<LevelTransition>,
+  mut level_selection: ResMut<LevelSelection>,
+  overlays: Query<Entity, With<FadeOverlay>>,
+  time: Res<Time>,
+) {
+  match &mut *transition {
+    LevelTransition::FadingOut {
+      timer,
+      target_level,
+      target_entry,
+    } => {
+      if timer.tick(time.delta()).finished() {
+        *level_selection = LevelSelection::Identifier(target_level.clone());
+        *transition = LevelTransition::Switching {
+          target_level: target_level.clone(),
+          target_entry: target_entry.clone(),
+        };
+      }
+    }
+    LevelTransition::FadingIn { timer } => {
+      if timer.tick(time.delta()).finished() {
+        *transition = LevelTransition::None;
+        for overlay in overlays.iter() {
+          commands.entity(overlay).despawn_recursive();
+        }
+      }
+    }
+    LevelTransition::Switching { .. } | LevelTransition::None => {}
+  }
+}
+
+/// Moves the player to the target entry point as soon as the target level is the selected one and
+/// its entities have spawned. Only entry points of the target level count, so levels can reuse
+/// names. The walkability grid and wall colliders follow the level selection on their own.
+fn place_player_at_entry(
+  mut transition: ResMut<LevelTransition>,
+  entry_points: Query<(&EntryPoint, &Transform, &Parent), Without<Player>>,
+  layers: Query<&Parent, Without<EntryPoint>>,
+  mut players: Query<&mut Transform, With<Player>>,
+  level_selection: Res<LevelSelection>,
+  level_query: Query<
+    (Entity, &Handle<LdtkLevel>, &Transform),
+    (Without<Player>, Without<EntryPoint>),
+  >,
+  ldtk_levels: Res<Assets<LdtkLevel>>,
+) {
+  let LevelTransition::Switching {
+    target_level,
+    target_entry,
+  } = &*transition
+  else {
+    return;
+  };
+
+  let Some(ldtk_level) = selected_level(
+    &level_selection,
+    level_query.iter().map(|(_, level_handle, _)| level_handle),
+    &ldtk_levels,
+  ) else {
+    return;
+  };
+
+  if ldtk_level.level.identifier != *target_level {
+    return;
+  }
+
+  let Some((level_entity, level_translation)) = level_query
+    .iter()
+    .find(|(_, level_handle, _)| {
+      ldtk_levels
+        .get(level_handle)
+        .map(|other| other.level.iid == ldtk_level.level.iid)
+        .unwrap_or(false)
+    })
+    .map(|(level_entity, _, level_transform)| (level_entity, level_transform.translation))
+  else {
+    return;
+  };
+
+  // Entry points are spawned relative to their level, as children of its entity layer
+  let Some((_, entry_transform, _)) = entry_points.iter().find(|(entry_point, _, layer)| {
+    entry_point.name == *target_entry
+      && layers
+        .get(layer.get())
+        .map_or(false, |level| level.get() == level_entity)
+  }) else {
+    return;
+  };
+
+  for mut player_transform in players.iter_mut() {
+    let z = player_transform.translation.z;
+    player_transform.translation = (level_translation + entry_transform.translation)
+      .truncate()
+      .extend(z);
+  }
+
+  *transition = LevelTransition::FadingIn {
+    timer: Timer::from_seconds(FADE_DURATION, TimerMode::Once),
+  };
+}
+
+/// Drops any transition left over from a run that ended mid-fade.
+pub fn reset_transition(
+  mut commands: Commands,
+  mut transition: ResMut<LevelTransition>,
+  overlays: Query<Entity, With<FadeOverlay>>,
+) {
+  *transition = LevelTransition::None;
+  for overlay in overlays.iter() {
+    commands.entity(overlay).despawn_recursive();
+  }
+}
+
+fn update_fade_overlay(
+  transition: Res<LevelTransition>,
+  mut overlays: Query<&mut BackgroundColor, With<FadeOverlay>>,
+) {
+  let alpha = match &*transition {
+    LevelTransition::FadingOut { timer, .. } => timer.percent(),
+    LevelTransition::Switching { .. } => 1.,
+    LevelTransition::FadingIn { timer } => timer.percent_left(),
+    LevelTransition::None => 0.,
+  };
+
+  for mut background_color in overlays.iter_mut() {
+    background_color.0.set_a(alpha);
+  }
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+
+pub mod plugin;
+pub mod systems;
+
+pub const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+pub const PANEL_COLOR: Color = Color::rgba(0.08, 0.08, 0.12, 0.9);
+pub const BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.28);
+pub const BUTTON_HOVERED_COLOR: Color = Color::rgb(0.3, 0.3, 0.4);
+pub const BUTTON_PRESSED_COLOR: Color = Color::rgb(0.4, 0.4, 0.55);
+
+#[derive(AssetCollection, Resource)]
+pub struct UiAssets {
+  #[asset(path = "fonts/DejaVuSans.ttf")]
+  pub font: Handle<Font>,
+}
+
+impl UiAssets {
+  pub fn text(&self, value: impl Into<String>, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+      value,
+      TextStyle {
+        font: self.font.clone(),
+        font_size,
+        color: TEXT_COLOR,
+      },
+    )
+  }
+}
+
+/// A full screen node that centers its children, used as the root of every menu screen.
+pub fn overlay(background: Color) -> NodeBundle {
+  NodeBundle {
+    style: Style {
+      size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+      position_type: PositionType::Absolute,
+      flex_direction: FlexDirection::Column,
+      justify_content: JustifyContent::Center,
+      align_items: AlignItems::Center,
+      ..default()
+    },
+    background_color: background.into(),
+    ..default()
+  }
+}
+
+/// A vertical panel holding the content of a menu.
+pub fn panel() -> NodeBundle {
+  NodeBundle {
+    style: Style {
+      flex_direction: FlexDirection::Column,
+      align_items: AlignItems::Stretch,
+      padding: UiRect::all(Val::Px(16.)),
+      min_size: Size::new(Val::Px(320.), Val::Auto),
+      ..default()
+    },
+    background_color: PANEL_COLOR.into(),
+    ..default()
+  }
+}
+
+pub fn button() -> ButtonBundle {
+  ButtonBundle {
+    style: Style {
+      justify_content: JustifyContent::Center,
+      align_items: AlignItems::Center,
+      padding: UiRect::new(Val::Px(12.), Val::Px(12.), Val::Px(4.), Val::Px(4.)),
+      margin: UiRect::all(Val::Px(4.)),
+      ..default()
+    },
+    background_color: BUTTON_COLOR.into(),
+    ..default()
+  }
+}
@@ -0,0 +1 @@
+crate::add_all_systems!(All);
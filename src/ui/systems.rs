@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+
+use super::{BUTTON_COLOR, BUTTON_HOVERED_COLOR, BUTTON_PRESSED_COLOR};
+
+pub fn add_systems() -> SystemSet {
+  SystemSet::new()
+    .label("ui")
+    .with_system(highlight_buttons.label("ui-highlight-buttons"))
+}
+
+fn highlight_buttons(
+  mut buttons: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+) {
+  for (interaction, mut background_color) in buttons.iter_mut() {
+    *background_color = match interaction {
+      Interaction::Clicked => BUTTON_PRESSED_COLOR,
+      Interaction::Hovered => BUTTON_HOVERED_COLOR,
+      Interaction::None => BUTTON_COLOR,
+    }
+    .into();
+  }
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::FieldValue, prelude::*};
+
+/// Looks up a custom field of an LDtk entity instance by its identifier.
+pub fn field<'a>(entity_instance: &'a EntityInstance, identifier: &str) -> Option<&'a FieldValue> {
+  entity_instance
+    .field_instances
+    .iter()
+    .find(|field_instance| field_instance.identifier == identifier)
+    .map(|field_instance| &field_instance.value)
+}
+
+pub fn field_int(entity_instance: &EntityInstance, identifier: &str) -> Option<i32> {
+  match field(entity_instance, identifier) {
+    Some(FieldValue::Int(value)) => *value,
+    _ => None,
+  }
+}
+
+pub fn field_float(entity_instance: &EntityInstance, identifier: &str) -> Option<f32> {
+  match field(entity_instance, identifier) {
+    Some(FieldValue::Float(value)) => *value,
+    Some(FieldValue::Int(value)) => value.map(|value| value as f32),
+    _ => None,
+  }
+}
+
+pub fn field_bool(entity_instance: &EntityInstance, identifier: &str) -> Option<bool> {
+  match field(entity_instance, identifier) {
+    Some(FieldValue::Bool(value)) => Some(*value),
+    _ => None,
+  }
+}
+
+/// Returns the value of a `String` (or `Enum`) field.
+pub fn field_string(entity_instance: &EntityInstance, identifier: &str) -> Option<String> {
+  match field(entity_instance, identifier) {
+    Some(FieldValue::String(value)) | Some(FieldValue::Enum(value)) => value.clone(),
+    _ => None,
+  }
+}
+
+/// Returns the non-null values of a `Array<String>` (or `Array<Enum>`) field.
+pub fn field_strings(entity_instance: &EntityInstance, identifier: &str) -> Vec<String> {
+  match field(entity_instance, identifier) {
+    Some(FieldValue::Strings(values)) | Some(FieldValue::Enums(values)) => {
+      values.iter().flatten().cloned().collect()
+    }
+    _ => Vec::new(),
+  }
+}
+
+/// Returns the loaded level matching the current [`LevelSelection`], if any.
+pub fn selected_level<'a>(
+  level_selection: &LevelSelection,
+  level_handles: impl IntoIterator<Item = &'a Handle<LdtkLevel>>,
+  ldtk_levels: &'a Assets<LdtkLevel>,
+) -> Option<&'a LdtkLevel> {
+  level_handles
+    .into_iter()
+    .filter_map(|level_handle| ldtk_levels.get(level_handle))
+    .find(|ldtk_level| level_selection.is_match(&0, &ldtk_level.level))
+}
@@ -1,2 +1,4 @@
+pub mod ldtk;
 pub mod macros;
 pub mod position;
+pub mod ron_asset;
@@ -0,0 +1,63 @@
+use std::marker::PhantomData;
+
+use bevy::{
+  asset::{Asset, AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+  prelude::*,
+};
+use serde::de::DeserializeOwned;
+
+/// Loads any deserializable asset from a RON file, e.g. `game.items.ron` or `game.dialogue.ron`.
+///
+/// Every asset type registers its own compound extension, so plain `.ron` files stay free for
+/// other loaders. Bevy only matches what follows a dot in the file name, so the files need a name
+/// before the extension: `items.ron` alone would be looked up as `ron`.
+pub struct RonAssetLoader<T> {
+  extensions: &'static [&'static str],
+  _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetLoader<T> {
+  pub fn new(extensions: &'static [&'static str]) -> Self {
+    Self {
+      extensions,
+      _marker: PhantomData,
+    }
+  }
+}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<T> {
+  fn load<'a>(
+    &'a self,
+    bytes: &'a [u8],
+    load_context: &'a mut LoadContext,
+  ) -> BoxedFuture<'a, Result<(), Error>> {
+    Box::pin(async move {
+      let asset = ron::de::from_bytes::<T>(bytes)?;
+      load_context.set_default_asset(LoadedAsset::new(asset));
+      Ok(())
+    })
+  }
+
+  fn extensions(&self) -> &[&str] {
+    self.extensions
+  }
+}
+
+pub trait RonAssetAppExt {
+  /// Registers `T` as an asset loaded from files ending in one of `extensions`.
+  fn add_ron_asset<T: Asset + DeserializeOwned>(
+    &mut self,
+    extensions: &'static [&'static str],
+  ) -> &mut Self;
+}
+
+impl RonAssetAppExt for App {
+  fn add_ron_asset<T: Asset + DeserializeOwned>(
+    &mut self,
+    extensions: &'static [&'static str],
+  ) -> &mut Self {
+    self
+      .add_asset::<T>()
+      .add_asset_loader(RonAssetLoader::<T>::new(extensions))
+  }
+}